@@ -0,0 +1,23 @@
+//! Export the version of the concrete crate resolved in Cargo.lock as CONCRETE_VERSION,
+//! so that pyconcrete.concrete_version() reports the library that is actually linked
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    let lock = Path::new(&env::var("CARGO_MANIFEST_DIR").unwrap()).join("Cargo.lock");
+    println!("cargo:rerun-if-changed={}", lock.display());
+    let version = fs::read_to_string(&lock).ok()
+        .and_then(|content| locked_version(&content, "concrete"))
+        .unwrap_or_else(|| "unknown".to_string());
+    println!("cargo:rustc-env=CONCRETE_VERSION={}", version);
+}
+
+/// Version of the package `name` in the content of a Cargo.lock
+fn locked_version(lock: &str, name: &str) -> Option<String> {
+    let name_line = format!("name = \"{}\"", name);
+    let mut lines = lock.lines().map(str::trim);
+    lines.find(|line| *line == name_line)?;
+    let version = lines.next()?.strip_prefix("version = \"")?.strip_suffix('"')?;
+    Some(version.to_string())
+}
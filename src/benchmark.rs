@@ -0,0 +1,74 @@
+//! benchmark module
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use pyo3::wrap_pyfunction;
+use concrete;
+use concrete::CryptoAPIError;
+use std::time::Instant;
+
+use super::translate_error;
+
+/// Bootstrap a dummy LWE ciphertext `n_iters` times
+/// The GIL is held: the key is borrowed rather than copied, and its fft buffers cannot be used
+/// from another thread
+///
+/// # Arguments
+/// * `bsk` - the bootstrapping key
+/// * `n_iters` - the number of bootstraps to run, at least 1
+///
+/// # Output
+/// * the average wall-clock time of one bootstrap in milliseconds
+#[pyfunction]
+pub fn benchmark_bootstrap(bsk: &crate::LWEBSK, n_iters: usize) -> PyResult<f64> {
+    if n_iters == 0 {
+        return Err(PyValueError::new_err("n_iters must be at least 1"));
+    }
+    // the blind rotation skips the null coefficients of the mask, so the dummy ciphertext is
+    // an actual encryption under a throwaway key rather than a zero ciphertext
+    let ct = dummy_ciphertext(bsk.data.get_lwe_dimension())?;
+    let start = Instant::now();
+    for _ in 0..n_iters {
+        translate_error!(ct.bootstrap(&bsk.data))?;
+    }
+    Ok(start.elapsed().as_secs_f64() * 1000. / n_iters as f64)
+}
+
+/// Key switch a dummy LWE ciphertext `n_iters` times with the GIL released
+///
+/// # Arguments
+/// * `ksk` - the key switching key
+/// * `n_iters` - the number of key switches to run, at least 1
+///
+/// # Output
+/// * the average wall-clock time of one key switch in milliseconds
+#[pyfunction]
+pub fn benchmark_keyswitch(py: Python, ksk: &crate::LWEKSK, n_iters: usize) -> PyResult<f64> {
+    if n_iters == 0 {
+        return Err(PyValueError::new_err("n_iters must be at least 1"));
+    }
+    let ct = dummy_ciphertext(ksk.data.dimension_before)?;
+    let ksk = &ksk.data;
+    let elapsed = py.allow_threads(move || {
+        let start = Instant::now();
+        for _ in 0..n_iters {
+            ct.keyswitch(ksk)?;
+        }
+        Ok::<_, CryptoAPIError>(start.elapsed())
+    });
+    let elapsed = translate_error!(elapsed)?;
+    Ok(elapsed.as_secs_f64() * 1000. / n_iters as f64)
+}
+
+/// Encrypt 0 under a fresh secret key of the given dimension, so that the mask is random
+fn dummy_ciphertext(dimension: usize) -> PyResult<concrete::LWE> {
+    let sk = concrete::LWESecretKey::new(&concrete::LWEParams::new(dimension, -30));
+    let encoder = translate_error!(concrete::Encoder::new(0., 1., 2, 1))?;
+    translate_error!(concrete::LWE::encode_encrypt(&sk, 0., &encoder))
+}
+
+pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_function(wrap_pyfunction!(benchmark_bootstrap, m)?)?;
+    m.add_function(wrap_pyfunction!(benchmark_keyswitch, m)?)?;
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ use super::{translate_error, Plaintext};
 /// * `delta` - the delta of the encoding
 /// * `nb_bit_precision` - the minimum number of bits to represent a plaintext
 /// * `nb_bit_padding` - the number of bits set to zero in the MSB
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Encoder {
     // pub o: f64,     // with margin between 1 and 0
@@ -301,6 +301,71 @@ impl Encoder {
         Ok(Plaintext{ data })
     }
 
+    /// Encode several messages with this Encoder and directly encrypt them into a VectorLWE
+    /// (same as VectorLWE.encode_encrypt)
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `messages`- a list of messages as a f64
+    /// # Output
+    /// * a new VectorLWE
+    pub fn encrypt(
+        &self,
+        sk: &crate::LWESecretKey,
+        messages: Vec<f64>,
+    ) -> PyResult<crate::VectorLWE> {
+        crate::VectorLWE::encode_encrypt(sk, messages, self)
+    }
+
+    /// Instantiate a new Encoder for a logarithmic encoding of the interval [min,max[
+    /// The linear encoding is done on [ln(min),ln(max)[, so messages have to go through
+    /// encode_log and decode_log. Note that adding log-encoded ciphertexts multiplies the
+    /// messages and multiplying them by a constant raises the messages to a power
+    /// # Arguments
+    /// * `min`- the minimum real value of the interval, strictly positive
+    /// * `max`- the maximum real value of the interval
+    /// * `nb_bit_precision` - number of bits to represent a plaintext
+    /// * `nb_bit_padding` - number of bits for left padding with zeros
+    /// # Output
+    /// * a new instantiation of an Encoder
+    #[staticmethod]
+    pub fn new_log_scale(
+        min: f64,
+        max: f64,
+        nb_bit_precision: usize,
+        nb_bit_padding: usize,
+    ) -> PyResult<Encoder> {
+        if min <= 0. {
+            return Err(PyValueError::new_err(format!(
+                "a logarithmic encoding needs a strictly positive min, got {}", min)));
+        }
+        let data = translate_error!(concrete::Encoder::new(
+            min.ln(), max.ln(), nb_bit_precision, nb_bit_padding))?;
+        Ok(Encoder{ data })
+    }
+
+    /// Encode several messages with a logarithmic encoding (see new_log_scale)
+    /// # Arguments
+    /// * `messages`- a list of strictly positive messages as a f64
+    pub fn encode_log(&self, messages: Vec<f64>) -> PyResult<Plaintext> {
+        if let Some(m) = messages.iter().find(|m| **m <= 0.) {
+            return Err(PyValueError::new_err(format!(
+                "a logarithmic encoding needs strictly positive messages, got {}", m)));
+        }
+        let logs: Vec<f64> = messages.iter().map(|m| m.ln()).collect();
+        let data = translate_error!(self.data.encode(&logs))?;
+        Ok(Plaintext{ data })
+    }
+
+    /// Decode one single plaintext with a logarithmic encoding (see new_log_scale)
+    /// # Arguments
+    /// * `ec` - an plaintext
+    /// # Output
+    /// * the decoded value as a f64
+    pub fn decode_log(&self, ec: Torus) -> PyResult<f64> {
+        let log = translate_error!(self.data.decode_single(ec))?;
+        Ok(log.exp())
+    }
+
     /// Computes the smallest real number that this encoding can handle
     pub fn get_granularity(&self) -> f64 {
         self.data.delta / f64::powi(2., self.data.nb_bit_precision as i32)
@@ -318,6 +383,72 @@ impl Encoder {
         self.data.delta - self.data.get_granularity()
     }
 
+    /// Check if a message can be encoded, i.e. if it is inside the interval [min, min + delta[
+    /// This is larger than [min, max] by almost one granularity, as the encoding of a message
+    /// between max and min + delta is still valid
+    pub fn contains(&self, message: f64) -> bool {
+        message >= self.data.o && message < self.data.o + self.data.delta
+    }
+
+    /// Computes a copy of this encoding with the smallest precision whose granularity is at most
+    /// `granularity`, keeping the same interval and padding
+    /// # Argument
+    /// * `granularity` - the requested granularity, strictly positive
+    /// # Output
+    /// * a new Encoder
+    /// * ValueError if the precision would not fit in a Torus element along with the padding
+    pub fn with_granularity(&self, granularity: f64) -> PyResult<Encoder> {
+        if granularity <= 0. {
+            return Err(PyValueError::new_err(format!(
+                "the granularity must be strictly positive, got {}", granularity)));
+        }
+        let nb_bit_precision = f64::log2(self.data.delta / granularity).ceil().max(1.) as usize;
+        if nb_bit_precision + self.data.nb_bit_padding > <Torus>::BITS as usize {
+            return Err(PyValueError::new_err(format!(
+                "a granularity of {} needs {} bits of precision, which does not fit with {} bits of padding",
+                granularity, nb_bit_precision, self.data.nb_bit_padding)));
+        }
+        let mut data = self.data.clone();
+        data.nb_bit_precision = nb_bit_precision;
+        Ok(Encoder{ data })
+    }
+
+    /// Computes a copy of this encoding where `nb` bits of padding become bits of precision,
+    /// keeping the same interval, so the granularity is divided by 2^nb
+    /// Only the encoder changes, ciphertexts are not touched
+    /// # Argument
+    /// * `nb` - the number of bits moved
+    /// # Output
+    /// * a new Encoder
+    /// * ValueError if there are less than `nb` bits of padding
+    pub fn move_padding_to_precision(&self, nb: usize) -> PyResult<Encoder> {
+        if nb > self.data.nb_bit_padding {
+            return Err(PyValueError::new_err(format!(
+                "cannot move {} bits of padding, only {} are left", nb, self.data.nb_bit_padding)));
+        }
+        let mut data = self.data.clone();
+        data.nb_bit_padding -= nb;
+        data.nb_bit_precision += nb;
+        Ok(Encoder{ data })
+    }
+
+    /// Computes the number of values that this encoding can represent
+    /// # Output
+    /// * 2^nb_bit_precision
+    /// * ValueError if this number does not fit in a usize
+    pub fn num_levels(&self) -> PyResult<usize> {
+        Encoder::levels_of(&self.data)
+    }
+
+    /// Computes the representable value closest to a message, clamped to the interval
+    pub fn snap(&self, message: f64) -> f64 {
+        let granularity = self.data.get_granularity();
+        // computed on floats, as the number of levels may not fit in a usize
+        let max_level = f64::powf(2., self.data.nb_bit_precision as f64) - 1.;
+        let level = ((message - self.data.o) / granularity).round().clamp(0., max_level);
+        self.data.o + level * granularity
+    }
+
     /// Copy the content of the input encoder inside the self encoder
     /// # Argument
     /// * `encoder`- the encoder to be copied
@@ -366,6 +497,25 @@ impl Encoder {
         Ok(Encoder{ data })
     }
 
+    /// Create a new encoder whose interval contains every product of a message of self with
+    /// a message of other, i.e. the hull of the products of the interval bounds
+    /// The precision is the smallest of the two precisions
+    /// # Arguments
+    /// * `other` - the encoder of the other factor
+    /// * `nb_bit_padding` - number of bits of padding of the output encoder
+    /// # Output
+    /// * a new Encoder
+    pub fn for_product(&self, other: &Encoder, nb_bit_padding: usize) -> PyResult<Encoder> {
+        let (a_min, a_max) = (self.get_min(), self.get_max());
+        let (b_min, b_max) = (other.get_min(), other.get_max());
+        let products = [a_min * b_min, a_min * b_max, a_max * b_min, a_max * b_max];
+        let min = products.iter().cloned().fold(f64::INFINITY, f64::min);
+        let max = products.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let nb_bit_precision = usize::min(self.data.nb_bit_precision, other.data.nb_bit_precision);
+        let data = translate_error!(concrete::Encoder::new(min, max, nb_bit_precision, nb_bit_padding))?;
+        Ok(Encoder{ data })
+    }
+
     /// Wrap the core_api encode function with the padding
     /// # Argument
     /// * `m` - the message to encode
@@ -446,6 +596,30 @@ impl Encoder {
         !(self.data.nb_bit_precision == 0 || self.data.delta <= 0.)
     }
 
+    /// Computes how many bits of precision a message actually needs in this encoding, i.e. the
+    /// position of the most significant set bit of its encoded value counted from the min
+    /// # Argument
+    /// * `message` - a message inside the interval of the encoder
+    /// # Output
+    /// * a number of bits between 0 and nb_bit_precision
+    /// * MessageOutsideIntervalError if the message is not in the interval
+    pub fn bits_used_by(&self, message: f64) -> PyResult<usize> {
+        let pt = translate_error!(self.data.encode_core(message))?;
+        let used = self.data.nb_bit_padding + self.data.nb_bit_precision;
+        let level = if used >= <Torus>::BITS as usize {
+            pt
+        } else {
+            let shift = <Torus>::BITS as usize - used;
+            // round to the closest level and drop the noise-free low bits
+            (pt >> shift) + ((pt >> (shift - 1)) & 1)
+        };
+        let max_level = (1 as Torus)
+            .checked_shl(self.data.nb_bit_precision as u32)
+            .map_or(Torus::MAX, |v| v - 1);
+        let level = level.min(max_level);
+        Ok((<Torus>::BITS - level.leading_zeros()) as usize)
+    }
+
     pub fn save(&self, path: &str) -> PyResult<()> {
         translate_error!(self.data.save(path))
     }
@@ -456,6 +630,42 @@ impl Encoder {
         Ok(Encoder{ data })
     }
 
+    /// Export the Encoder as a plain tuple
+    /// # Output
+    /// * a tuple (o, delta, nb_bit_precision, nb_bit_padding, round)
+    pub fn as_tuple(&self) -> (f64, f64, usize, usize, bool) {
+        (
+            self.data.o,
+            self.data.delta,
+            self.data.nb_bit_precision,
+            self.data.nb_bit_padding,
+            self.data.round,
+        )
+    }
+
+    /// Rebuild an Encoder from a tuple produced by as_tuple
+    /// # Argument
+    /// * `t` - a tuple (o, delta, nb_bit_precision, nb_bit_padding, round)
+    /// # Output
+    /// * a new Encoder
+    /// * ValueError - if o or delta is not finite, delta <= 0 or nb_bit_precision + nb_bit_padding > 64
+    #[staticmethod]
+    pub fn from_tuple(t: (f64, f64, usize, usize, bool)) -> PyResult<Encoder> {
+        let (o, delta, nb_bit_precision, nb_bit_padding, round) = t;
+        if !o.is_finite() || !delta.is_finite() || delta <= 0. {
+            return Err(PyValueError::new_err(format!(
+                "invalid interval: o = {} and delta = {} (both must be finite and delta > 0)", o, delta)));
+        }
+        if nb_bit_precision.checked_add(nb_bit_padding).is_none_or(|bits| bits > 64) {
+            return Err(PyValueError::new_err(format!(
+                "nb_bit_precision = {} and nb_bit_padding = {} do not fit in 64 bits",
+                nb_bit_precision, nb_bit_padding)));
+        }
+        Ok(Encoder {
+            data: concrete::Encoder { o, delta, nb_bit_precision, nb_bit_padding, round },
+        })
+    }
+
     /// Modify the encoding to be use after an homomorphic opposite
     /// ```rust
     /// use concrete::Encoder;
@@ -480,11 +690,33 @@ impl Encoder {
         Ok(())
     }
 
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<Encoder, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<Encoder> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(Encoder{ data })
+    }
+
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
 }
 
+impl Encoder {
+    /// Number of values an encoder can represent, 2^nb_bit_precision
+    pub(crate) fn levels_of(encoder: &concrete::Encoder) -> PyResult<usize> {
+        u32::try_from(encoder.nb_bit_precision).ok()
+            .and_then(|precision| 1usize.checked_shl(precision))
+            .ok_or_else(|| PyValueError::new_err(format!(
+                "2^{} levels do not fit in a usize", encoder.nb_bit_precision)))
+    }
+}
+
 
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<Encoder>()?;
@@ -1,4 +1,7 @@
 use pyo3::prelude::*;
+use pyo3::type_object::PyTypeInfo;
+use pyo3::types::PyBytes;
+use pyo3::wrap_pyfunction;
 #[allow(unused_imports)]
 use pyo3::exceptions::PyValueError;
 // use pyo3::{wrap_pyfunction, wrap_pymodule};
@@ -25,6 +28,10 @@ pub mod lwe_ksk;
 pub use lwe_ksk::LWEKSK;
 pub mod lwe_bsk;
 pub use lwe_bsk::LWEBSK;
+pub mod rlwe_ksk;
+pub use rlwe_ksk::RLWEKSK;
+pub mod multivalue_lut;
+pub use multivalue_lut::MultivalueLUT;
 
 pub mod lwe;
 pub use lwe::LWE;
@@ -33,6 +40,8 @@ pub use vector_lwe::VectorLWE;
 pub mod vector_rlwe;
 pub use vector_rlwe::VectorRLWE;
 
+pub mod benchmark;
+
 
 #[macro_export]
 macro_rules! translate_error {
@@ -44,12 +53,124 @@ macro_rules! translate_error {
     };
 }
 
+/// Version of the concrete crate this module is built against, read from Cargo.lock by build.rs
+const CONCRETE_VERSION: &str = env!("CONCRETE_VERSION");
+
+/// Return the version of pyconcrete
+#[pyfunction]
+fn version() -> &'static str {
+    env!("CARGO_PKG_VERSION")
+}
+
+/// Return the version of the concrete library pyconcrete is built against
+#[pyfunction]
+fn concrete_version() -> &'static str {
+    CONCRETE_VERSION
+}
+
+/// Initialize the global thread pool used by the parallel operations with `n` threads
+/// Calling it again with the same number is a no-op, any other number raises an error
+#[pyfunction]
+fn set_num_threads(n: usize) -> PyResult<()> {
+    if n == 0 {
+        return Err(PyValueError::new_err("the number of threads must be at least 1"));
+    }
+    if rayon::ThreadPoolBuilder::new().num_threads(n).build_global().is_err() {
+        let current = rayon::current_num_threads();
+        if current != n {
+            return Err(PyValueError::new_err(format!(
+                "the thread pool is already initialized with {} threads", current)));
+        }
+    }
+    Ok(())
+}
+
+/// Return the number of threads of the global thread pool
+#[pyfunction]
+fn num_threads() -> usize {
+    rayon::current_num_threads()
+}
+
 pub(crate) fn helper_is_int(value: f64) -> bool {
     let ivalue = value as i32;
     let remainder = value - ivalue as f64;
-    return remainder == 0.0;
+    remainder == 0.0
+}
+
+pub(crate) fn helper_deltas_eq(delta1: f64, delta2: f64) -> bool {
+    (delta1 - delta2).abs() <= 1e-10f64 * delta1.abs().max(delta2.abs())
+}
+
+/// Build the (callable, args) pair of __reduce__, rebuilding the object with `T._from_bytes`
+pub(crate) fn helper_reduce<T: PyTypeInfo, D: serde::Serialize>(
+    py: Python, data: &D,
+) -> PyResult<(PyObject, (PyObject,))> {
+    let bytes = translate_error!(bincode::serialize(data))?;
+    let constructor = py.get_type::<T>().getattr("_from_bytes")?;
+    Ok((constructor.into(), (PyBytes::new(py, &bytes).into(),)))
 }
 
+/// Run `f`, turning a panic of the underlying library into a ValueError
+/// (used for the concrete functions that unwrap their errors, like LWEBSK::load)
+/// `f` must not leave shared state half modified when it panics
+pub(crate) fn helper_catch_panic<R, F: FnOnce() -> R>(f: F) -> PyResult<R> {
+    std::panic::catch_unwind(std::panic::AssertUnwindSafe(f)).map_err(|e| {
+        let msg = e.downcast_ref::<&str>().map(|s| s.to_string())
+            .or_else(|| e.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown error".to_string());
+        PyValueError::new_err(msg)
+    })
+}
+
+/// A read-only memory mapping of a whole file, unmapped on drop
+pub(crate) struct MappedFile {
+    ptr: *mut libc::c_void,
+    len: usize,
+}
+
+impl MappedFile {
+    pub(crate) fn as_slice(&self) -> &[u8] {
+        // the mapping is valid and read-only for the lifetime of self
+        unsafe { std::slice::from_raw_parts(self.ptr as *const u8, self.len) }
+    }
+}
+
+#[cfg(unix)]
+impl Drop for MappedFile {
+    fn drop(&mut self) {
+        unsafe { libc::munmap(self.ptr, self.len); }
+    }
+}
+
+/// Memory-map `file` read-only and private (copy-on-write), so that the mapping can never write
+/// back to the file, while its clean pages are still shared with other processes by the page cache
+/// The file must not be modified while it is mapped: a write may show up in the mapping, and
+/// reading past the end of a file truncated in the meantime raises SIGBUS
+#[cfg(unix)]
+pub(crate) fn helper_mmap(file: &std::fs::File) -> std::io::Result<MappedFile> {
+    use std::os::unix::io::AsRawFd;
+    let len = file.metadata()?.len() as usize;
+    if len == 0 {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, "empty file"));
+    }
+    let ptr = unsafe {
+        libc::mmap(std::ptr::null_mut(), len, libc::PROT_READ, libc::MAP_PRIVATE, file.as_raw_fd(), 0)
+    };
+    if ptr == libc::MAP_FAILED {
+        return Err(std::io::Error::last_os_error());
+    }
+    Ok(MappedFile { ptr, len })
+}
+
+#[cfg(not(unix))]
+pub(crate) fn helper_mmap(_file: &std::fs::File) -> std::io::Result<MappedFile> {
+    Err(std::io::Error::new(std::io::ErrorKind::Unsupported, "mmap is only supported on unix"))
+}
+
+/// Deserialize the bytes produced by helper_reduce
+pub(crate) fn helper_from_bytes<D: serde::de::DeserializeOwned>(bytes: &[u8]) -> PyResult<D> {
+    translate_error!(bincode::deserialize(bytes))
+}
 
 #[pymodule]
 fn pyconcrete(py: Python, m: &PyModule) -> PyResult<()> {
@@ -66,11 +187,20 @@ fn pyconcrete(py: Python, m: &PyModule) -> PyResult<()> {
 
     lwe_ksk::register(py, m)?;
     lwe_bsk::register(py, m)?;
+    rlwe_ksk::register(py, m)?;
+    multivalue_lut::register(py, m)?;
     
     lwe::register(py, m)?;
     vector_lwe::register(py, m)?;
     vector_rlwe::register(py, m)?;
 
+    benchmark::register(py, m)?;
+
+    m.add_function(wrap_pyfunction!(version, m)?)?;
+    m.add_function(wrap_pyfunction!(concrete_version, m)?)?;
+    m.add_function(wrap_pyfunction!(set_num_threads, m)?)?;
+    m.add_function(wrap_pyfunction!(num_threads, m)?)?;
+
     Ok(())
 }
 
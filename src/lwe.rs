@@ -4,9 +4,10 @@ use pyo3::exceptions::*;
 use pyo3::types::{PyFunction, PyAny}; //, PyInt, PyFloat};
 use concrete;
 use concrete::{Torus};
+use concrete_core::crypto::encoding::Plaintext;
 use concrete_core::math::tensor::{AsRefSlice, AsRefTensor};
 
-use super::{translate_error, helper_is_int};
+use super::{translate_error, helper_is_int, helper_deltas_eq};
 
 
 /// Structure containing a single LWE ciphertext.
@@ -16,7 +17,7 @@ use super::{translate_error, helper_is_int};
 /// * `variances` - the variance of the noise of the LWE ciphertext
 /// * `dimension` - the length the LWE mask
 /// * `encoder` - the encoder of the LWE ciphertext
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct LWE {
     // pub ciphertext: crypto::lwe::LweCiphertext<Vec<Torus>>,
@@ -79,11 +80,11 @@ impl LWE {
     }
 
     pub fn __add__(&self, o: &PyAny) -> PyResult<LWE> {
-        if let Some(o) = o.extract::<f64>().ok() {
+        if let Ok(o) = o.extract::<f64>() {
             // works for both PyInt and PyFloat
-            return translate_error!(self.add_constant_dynamic_encoder(o));
-        } else if let Some(o) = o.extract::<LWE>().ok() {
-            return translate_error!(self.add_with_padding(&o));
+            translate_error!(self.add_constant_dynamic_encoder(o))
+        } else if let Ok(o) = o.extract::<LWE>() {
+            translate_error!(self.add_with_padding(&o))
         } else {
             Err(PyTypeError::new_err("unsupported type for LWE addition"))
         }
@@ -94,30 +95,30 @@ impl LWE {
     }
 
     pub fn __iadd__(&mut self, o: &PyAny) -> PyResult<()> {
-        if let Some(o) = o.extract::<f64>().ok() {
-            return translate_error!(self.add_constant_dynamic_encoder_inplace(o));
-        } else if let Some(o) = o.extract::<LWE>().ok() {
-            return translate_error!(self.add_with_padding_inplace(&o));
+        if let Ok(o) = o.extract::<f64>() {
+            translate_error!(self.add_constant_dynamic_encoder_inplace(o))
+        } else if let Ok(o) = o.extract::<LWE>() {
+            translate_error!(self.add_with_padding_inplace(&o))
         } else {
             Err(PyTypeError::new_err("unsupported type for LWE addition"))
         }
     }
 
     pub fn __sub__(&self, o: &PyAny) -> PyResult<LWE> {
-        if let Some(o) = o.extract::<f64>().ok() {
-            return translate_error!(self.add_constant_dynamic_encoder(-o));
-        } else if let Some(o) = o.extract::<LWE>().ok() {
-            return translate_error!(self.sub_with_padding(&o));
+        if let Ok(o) = o.extract::<f64>() {
+            translate_error!(self.add_constant_dynamic_encoder(-o))
+        } else if let Ok(o) = o.extract::<LWE>() {
+            translate_error!(self.sub_with_padding(&o))
         } else {
             Err(PyTypeError::new_err("unsupported type for LWE subtraction"))
         }
     }
 
     pub fn __isub__(&mut self, o: &PyAny) -> PyResult<()> {
-        if let Some(o) = o.extract::<f64>().ok() {
-            return translate_error!(self.add_constant_dynamic_encoder_inplace(-o));
-        } else if let Some(o) = o.extract::<LWE>().ok() {
-            return translate_error!(self.sub_with_padding_inplace(&o));
+        if let Ok(o) = o.extract::<f64>() {
+            translate_error!(self.add_constant_dynamic_encoder_inplace(-o))
+        } else if let Ok(o) = o.extract::<LWE>() {
+            translate_error!(self.sub_with_padding_inplace(&o))
         } else {
             Err(PyTypeError::new_err("unsupported type for LWE subtraction"))
         }
@@ -155,6 +156,12 @@ impl LWE {
         self.data.ciphertext.as_tensor().as_slice().to_vec()
     }
 
+    /// Check if the ciphertext is trivial, i.e. its mask is all zeros so that its body is the
+    /// plaintext itself, readable without the secret key
+    pub fn is_trivial(&self) -> bool {
+        self.data.ciphertext.as_tensor().as_slice()[..self.data.dimension].iter().all(|x| *x == 0)
+    }
+
     /// Instantiate a new LWE filled with zeros from a dimension
     ///
     /// # Arguments
@@ -177,6 +184,29 @@ impl LWE {
         Ok(LWE{ data })
     }
 
+    /// Encode a message into a trivial LWE: the mask is all zeros and there is no noise, so the
+    /// body is the plaintext itself
+    /// It is NOT private, anyone can read the message without the secret key; it is meant for
+    /// public constants (e.g. the additive identity) combined with real ciphertexts
+    ///
+    /// # Arguments
+    /// * `message` - a f64 in the interval of the encoder
+    /// * `encoder` - an Encoder
+    /// * `dimension` - the length the LWE mask, that of the ciphertexts it will be combined with
+    ///
+    /// # Output
+    /// * a new LWE with a null variance
+    /// * ValueError - if the message is outside of the interval of the encoder
+    #[staticmethod]
+    pub fn trivial_encrypt(message: f64, encoder: &crate::Encoder, dimension: usize) -> PyResult<crate::LWE> {
+        let pt = translate_error!(encoder.data.encode_single(message))?;
+        let mut data = translate_error!(concrete::LWE::zero(dimension))?;
+        data.ciphertext.get_mut_body().0 = pt.plaintexts[0];
+        data.variance = 0.;
+        data.encoder = encoder.data.clone();
+        Ok(LWE{ data })
+    }
+
     /// Encode a message and then directly encrypt the plaintext into an LWE structure
     ///
     /// # Arguments
@@ -272,6 +302,24 @@ impl LWE {
         translate_error!(self.data.decrypt_decode(&sk.data))
     }
 
+    /// Decrypt the ciphertext without decoding, i.e. compute the noisy phase
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// # Output
+    /// * the phase as a Torus element
+    /// * DimensionError - if the ciphertext and the key have incompatible dimensions
+    pub fn decrypt_raw(&self, sk: &crate::LWESecretKey) -> PyResult<Torus> {
+        if sk.data.dimension != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the ciphertext has dimension {} but the secret key has dimension {}",
+                self.data.dimension, sk.data.dimension)));
+        }
+        let mut output = Plaintext(0);
+        sk.data.val.decrypt_lwe(&mut output, &self.data.ciphertext);
+        Ok(output.0)
+    }
+
     /// Decrypt the ciphertext, meaning compute the phase and directly decode the output as if the encoder was in a rounding context
     ///
     /// # Arguments
@@ -301,6 +349,27 @@ impl LWE {
         translate_error!(self.data.decrypt_decode_round(&sk.data))
     }
 
+    /// Decrypt the ciphertext, round it like decrypt_decode_round and return it as an integer,
+    /// for integer circuits where the encoder represents the integers exactly
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `tolerance` - the largest accepted distance between the rounded value and an integer
+    /// # Output
+    /// * the decrypted integer
+    /// * ValueError - if the rounded value is not within `tolerance` of an i64
+    /// * DimensionError - if the ciphertext and the key have incompatible dimensions
+    #[args(tolerance = "1e-6")]
+    pub fn decrypt_decode_int(&self, sk: &crate::LWESecretKey, tolerance: f64) -> PyResult<i64> {
+        let value = translate_error!(self.data.decrypt_decode_round(&sk.data))?;
+        let rounded = value.round();
+        if (value - rounded).abs() > tolerance || rounded < i64::MIN as f64 || rounded >= i64::MAX as f64 {
+            return Err(PyValueError::new_err(format!(
+                "the decrypted value {} is not within {} of an integer", value, tolerance)));
+        }
+        Ok(rounded as i64)
+    }
+
     /// Add a small message to a LWE ciphertext and does not change the encoding but changes the bodies of the ciphertext
     ///
     /// # Argument
@@ -1223,6 +1292,7 @@ impl LWE {
     }
 
     /// Compute a bootstrap on the LWE
+    /// The identity lookup table is cached on the bootstrapping key for the encoder of self
     ///
     /// # Argument
     /// * `bsk` - the bootstrapping key
@@ -1270,7 +1340,9 @@ impl LWE {
     ///     .unwrap();
     /// ```
     pub fn bootstrap(&self, bsk: &crate::LWEBSK) -> PyResult<crate::LWE> {
-        let data = translate_error!(self.data.bootstrap(&bsk.data))?;
+        let encoder = &self.data.encoder;
+        let table = translate_error!(bsk.lut_cache.identity_table(&bsk.data, encoder, encoder))?;
+        let data = translate_error!(crate::LWEBSK::bootstrap_with_table(&bsk.data, &self.data, &table, encoder))?;
         Ok(LWE{ data })
     }
 
@@ -1340,6 +1412,157 @@ impl LWE {
         Ok(LWE{ data })
     }
 
+    /// Raise the LWE ciphertext to a small integer power with one bootstrap
+    /// The power is evaluated in the lookup table, so it costs a single bootstrap whatever `k` is,
+    /// but `k` is limited to 8 since the output interval grows quickly with it
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `k` - the exponent, between 1 and 8
+    /// * `encoder_output` - the encoder of the output, which has to cover x^k over the input interval
+    ///
+    /// # Output
+    /// * a LWE struct
+    /// * ValueError - if `k` is out of bounds or `encoder_output` does not cover the output range
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn pow_bootstrap(
+        &self, bsk: &crate::LWEBSK, k: usize, encoder_output: &crate::Encoder,
+    ) -> PyResult<crate::LWE> {
+        if k == 0 || k > 8 {
+            return Err(PyValueError::new_err(format!("the exponent must be in [1, 8], got {}", k)));
+        }
+        let (min, max) = (self.data.encoder.get_min(), self.data.encoder.get_max());
+        let (pmin, pmax) = (min.powi(k as i32), max.powi(k as i32));
+        let mut range = (pmin.min(pmax), pmin.max(pmax));
+        if k.is_multiple_of(2) && min < 0. && max > 0. {
+            range.0 = 0.;
+        }
+        let tolerance = encoder_output.data.get_granularity() / 2.;
+        if range.0 < encoder_output.data.get_min() - tolerance
+            || range.1 > encoder_output.data.get_max() + tolerance
+        {
+            return Err(PyValueError::new_err(format!(
+                "the output encoder [{}, {}] does not cover x^{} over [{}, {}]",
+                encoder_output.data.get_min(), encoder_output.data.get_max(), k, range.0, range.1)));
+        }
+        let data = translate_error!(self.data.bootstrap_with_function(
+            &bsk.data, |x| x.powi(k as i32), &encoder_output.data))?;
+        Ok(LWE{ data })
+    }
+
+    /// Compute x mod m on an encrypted integer with one bootstrap, the result being in [0, m-1]
+    /// (Euclidean remainder, so -1 mod 3 = 2)
+    /// The input encoder has to represent the integers exactly: its minimum must be an integer
+    /// and its granularity must divide 1, and the lookup table holds one entry per representable
+    /// input, so the input interval is limited by the 2^nb_bit_precision levels of the encoder
+    /// The output encoder needs enough precision to tell apart the m values 0, ..., m-1
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `m` - the modulus, at least 1
+    /// * `encoder_output` - the encoder of the output, which has to cover [0, m-1]
+    ///
+    /// # Output
+    /// * a LWE struct
+    /// * ValueError - if `m` is not positive or an encoder is not suited for integers mod m
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn mod_constant(
+        &self, bsk: &crate::LWEBSK, m: i64, encoder_output: &crate::Encoder,
+    ) -> PyResult<crate::LWE> {
+        if m <= 0 {
+            return Err(PyValueError::new_err(format!("the modulus must be positive, got {}", m)));
+        }
+        let granularity = self.data.encoder.get_granularity();
+        let min = self.data.encoder.get_min();
+        let steps = 1. / granularity;
+        if (min - min.round()).abs() > granularity / 2. || (steps - steps.round()).abs() > 1e-6 {
+            return Err(PyValueError::new_err(format!(
+                "the input encoder (min {}, granularity {}) does not represent the integers exactly",
+                min, granularity)));
+        }
+        let tolerance = encoder_output.data.get_granularity() / 2.;
+        if encoder_output.data.get_min() > tolerance
+            || encoder_output.data.get_max() + tolerance < (m - 1) as f64
+        {
+            return Err(PyValueError::new_err(format!(
+                "the output encoder [{}, {}] does not cover [0, {}]",
+                encoder_output.data.get_min(), encoder_output.data.get_max(), m - 1)));
+        }
+        let data = translate_error!(self.data.bootstrap_with_function(
+            &bsk.data, |x| x.round().rem_euclid(m as f64), &encoder_output.data))?;
+        Ok(LWE{ data })
+    }
+
+    /// Bootstrap the LWE ciphertext through a function given as a table of outputs
+    /// The i-th entry of the table is the output for the i-th representable input,
+    /// i.e. min + i * granularity with the encoder of self
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `table` - the outputs, one for each of the 2^nb_bit_precision input levels
+    /// * `encoder_output` - the encoder of the output, which has to cover all the values of the table
+    ///
+    /// # Output
+    /// * a LWE struct
+    /// * ValueError - if the size of the table is wrong or a value is outside of `encoder_output`
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn apply_table(
+        &self, bsk: &crate::LWEBSK, table: Vec<f64>, encoder_output: &crate::Encoder,
+    ) -> PyResult<crate::LWE> {
+        let encoder = &self.data.encoder;
+        let nb_levels = crate::Encoder::levels_of(encoder)?;
+        if table.len() != nb_levels {
+            return Err(PyValueError::new_err(format!(
+                "the table must have 2^{} = {} entries, got {}",
+                encoder.nb_bit_precision, nb_levels, table.len())));
+        }
+        if let Some((i, v)) = table.iter().enumerate().find(|(_, v)| !encoder_output.contains(**v)) {
+            return Err(PyValueError::new_err(format!(
+                "table entry {} at index {} is outside of the interval [{}, {}[", v, i,
+                encoder_output.get_min(), encoder_output.get_min() + encoder_output.get_delta())));
+        }
+        let (min, granularity) = (encoder.o, encoder.get_granularity());
+        let lookup = |x: f64| {
+            let level = ((x - min) / granularity).round().max(0.) as usize;
+            table[level.min(nb_levels - 1)]
+        };
+        let data = translate_error!(self.data.bootstrap_with_function(
+            &bsk.data, lookup, &encoder_output.data))?;
+        Ok(LWE{ data })
+    }
+
+    /// Evaluate several functions of the LWE ciphertext with the tables of
+    /// LWEBSK.generate_multivalue_look_up_table, one bootstrap per function
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `mv_lut` - the MultivalueLUT built for the encoder of self
+    ///
+    /// # Output
+    /// * a VectorLWE with one ciphertext for each function, in the order of the functions
+    /// * ValueError - if the tables were built for another encoder (interval or precision) or for
+    ///   a bootstrapping key with another polynomial size
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn bootstrap_multivalue(
+        &self, bsk: &crate::LWEBSK, mv_lut: &crate::MultivalueLUT,
+    ) -> PyResult<crate::VectorLWE> {
+        if !mv_lut.applies_to(&self.data.encoder) {
+            return Err(PyValueError::new_err(format!(
+                "the tables were built for the encoder {} but the ciphertext has the encoder {}",
+                mv_lut.encoder_input, self.data.encoder)));
+        }
+        if mv_lut.get_polynomial_size() != bsk.data.polynomial_size {
+            return Err(PyValueError::new_err(format!(
+                "the tables have {} entries but the bootstrapping key has a polynomial size of {}",
+                mv_lut.get_polynomial_size(), bsk.data.polynomial_size)));
+        }
+        let cts = mv_lut.tables.iter().zip(mv_lut.encoders_output.iter())
+            .map(|(table, encoder)| crate::LWEBSK::bootstrap_with_table(&bsk.data, &self.data, table, encoder))
+            .collect::<Result<Vec<_>, String>>();
+        let data = crate::VectorLWE::from_lwes(&translate_error!(cts)?)?;
+        Ok(crate::VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
     /// Multiply two LWE ciphertexts thanks to two bootstrapping procedures
     /// need to have 2 bits of padding at least
     ///
@@ -1442,6 +1665,96 @@ impl LWE {
         translate_error!(self.data.remove_padding_inplace(nb))
     }
 
+    /// Return the number of bits of padding left in the ciphertext
+    pub fn padding_bits(&self) -> usize {
+        self.data.encoder.nb_bit_padding
+    }
+
+    /// Return true if the ciphertext has at least `nb` bits of padding left
+    pub fn has_padding(&self, nb: usize) -> bool {
+        self.data.encoder.nb_bit_padding >= nb
+    }
+
+    /// Encode and encrypt a boolean with the canonical boolean encoder, i.e. the interval [0,1]
+    /// with 2 bits of precision
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `value` - the boolean to encrypt
+    /// * `padding` - number of bits of padding, at least 2 to be usable in xor, and_ and or_
+    ///
+    /// # Output
+    /// an LWE structure encrypting 0. or 1.
+    #[staticmethod]
+    pub fn encrypt_bool(
+        sk: &crate::LWESecretKey,
+        value: bool,
+        padding: usize,
+    ) -> PyResult<LWE> {
+        let encoder = translate_error!(concrete::Encoder::new(0., 1., 2, padding))?;
+        let message = if value { 1. } else { 0. };
+        let data = translate_error!(concrete::LWE::encode_encrypt(&sk.data, message, &encoder))?;
+        Ok(LWE{ data })
+    }
+
+    /// Compute the exclusive or of two encrypted booleans (see encrypt_bool)
+    /// The two inputs are summed with add_with_padding and the sum goes through a bootstrap
+    /// The output is encrypted under the output key of the bootstrapping key
+    ///
+    /// # Arguments
+    /// * `ct` - an LWE struct encrypting a boolean with the same encoder as self
+    /// * `bsk` - the bootstrapping key
+    ///
+    /// # Output
+    /// * an LWE structure encrypting 0. or 1.
+    /// * NotEnoughPaddingError - if the inputs have less than 2 bits of padding
+    pub fn xor(&self, ct: &crate::LWE, bsk: &crate::LWEBSK) -> PyResult<crate::LWE> {
+        self.bool_gate(ct, bsk, |x| if x > 0.5 && x < 1.5 { 1. } else { 0. })
+    }
+
+    /// Compute the logical and of two encrypted booleans (see xor)
+    pub fn and_(&self, ct: &crate::LWE, bsk: &crate::LWEBSK) -> PyResult<crate::LWE> {
+        self.bool_gate(ct, bsk, |x| if x > 1.5 { 1. } else { 0. })
+    }
+
+    /// Compute the logical or of two encrypted booleans (see xor)
+    pub fn or_(&self, ct: &crate::LWE, bsk: &crate::LWEBSK) -> PyResult<crate::LWE> {
+        self.bool_gate(ct, bsk, |x| if x > 0.5 { 1. } else { 0. })
+    }
+
+    /// Homomorphically select between two ciphertexts: compute `b + selector * (a - b)`
+    /// The product with the selector is computed with bootstraps that exploit that the selector
+    /// is 0 or 1 (see gate), and b is refreshed with a bootstrap before the final addition
+    /// The gate bootstraps a sum of bootstrapped values, so when the bootstrapping key does not
+    /// map the dimension of the inputs to itself, a key switching key from its output key back
+    /// to its input key is needed
+    ///
+    /// # Arguments
+    /// * `selector` - an LWE struct encrypting 0 or 1 (e.g. from encrypt_bool)
+    /// * `a` - the value returned when the selector is 1
+    /// * `b` - the value returned when the selector is 0, with the same delta and padding as `a`
+    /// * `bsk` - the bootstrapping key
+    /// * `ksk` - the key switching key from the output key of `bsk` to its input key, only
+    /// optional when `bsk` maps the dimension of the inputs to itself
+    ///
+    /// # Output
+    /// * an LWE structure encrypting a or b under the output key of `bsk`, without padding left
+    /// * ValueError - if a and b have less than 2 bits of padding or the keys do not fit
+    #[staticmethod]
+    #[args(ksk = "None")]
+    pub fn select(
+        selector: &crate::LWE,
+        a: &crate::LWE,
+        b: &crate::LWE,
+        bsk: &crate::LWEBSK,
+        ksk: Option<&crate::LWEKSK>,
+    ) -> PyResult<crate::LWE> {
+        let ksk = ksk.map(|k| &k.data);
+        LWE::check_select(&selector.data, &a.data, &b.data, &bsk.data, ksk)?;
+        let data = translate_error!(LWE::select_unchecked(&selector.data, &a.data, &b.data, &bsk.data, ksk))?;
+        Ok(LWE{ data })
+    }
+
     pub fn save(&self, path: &str) -> PyResult<()> {
         translate_error!(self.data.save(path))
     }
@@ -1452,11 +1765,156 @@ impl LWE {
         Ok(LWE{ data })
     }
 
+    /// Return false: an LWE never holds secret key material, only the public ciphertext, its
+    /// variance and its encoder, so its pickle and its saved files are safe to share.
+    /// This is an invariant of the type, any field added to it must keep it
+    pub fn contains_secret(&self) -> bool {
+        false
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<LWE, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<LWE> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(LWE{ data })
+    }
+
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
 }
 
+impl LWE {
+    /// Check the inputs of select: they have the input dimension of the bsk, the ksk maps the
+    /// output dimension of the bsk back to it (or the bsk maps it to itself without ksk), the
+    /// selector has padding left and a, b share delta and at least 2 bits of padding
+    pub(crate) fn check_select(
+        selector: &concrete::LWE,
+        a: &concrete::LWE,
+        b: &concrete::LWE,
+        bsk: &concrete::LWEBSK,
+        ksk: Option<&concrete::LWEKSK>,
+    ) -> PyResult<()> {
+        let dimension_in = bsk.get_lwe_dimension();
+        let dimension_out = bsk.dimension * bsk.polynomial_size;
+        for ct in [selector, a, b].iter() {
+            if ct.dimension != dimension_in {
+                return Err(PyValueError::new_err(format!(
+                    "DimensionError: the bsk expects dimension {}, got {}", dimension_in, ct.dimension)));
+            }
+        }
+        match ksk {
+            Some(ksk) if ksk.dimension_before != dimension_out || ksk.dimension_after != dimension_in => {
+                return Err(PyValueError::new_err(format!(
+                    "the ksk must map dimension {} back to {}, got {} -> {}",
+                    dimension_out, dimension_in, ksk.dimension_before, ksk.dimension_after)));
+            }
+            None if dimension_out != dimension_in => {
+                return Err(PyValueError::new_err(format!(
+                    "the bsk maps dimension {} to {}, a ksk from {} back to {} is needed",
+                    dimension_in, dimension_out, dimension_out, dimension_in)));
+            }
+            _ => {}
+        }
+        if selector.encoder.nb_bit_padding < 1 {
+            return Err(PyValueError::new_err("the selector needs at least 1 bit of padding"));
+        }
+        if a.encoder.nb_bit_padding != b.encoder.nb_bit_padding
+            || !helper_deltas_eq(a.encoder.delta, b.encoder.delta) {
+            return Err(PyValueError::new_err("a and b must have the same delta and padding"));
+        }
+        if a.encoder.nb_bit_padding < 2 {
+            return Err(PyValueError::new_err(format!(
+                "at least 2 bits of padding are required, got {}", a.encoder.nb_bit_padding)));
+        }
+        Ok(())
+    }
+
+    /// Compute b + selector * (a - b) without checking the inputs (see check_select)
+    /// The output encoder covers both b and 0 or a - b, with a granularity at most the one of a
+    /// It does not need the GIL
+    pub(crate) fn select_unchecked(
+        selector: &concrete::LWE,
+        a: &concrete::LWE,
+        b: &concrete::LWE,
+        bsk: &concrete::LWEBSK,
+        ksk: Option<&concrete::LWEKSK>,
+    ) -> Result<concrete::LWE, String> {
+        let mut diff = a.sub_with_padding(b).map_err(|e| e.to_string())?;
+        // the interval doubled, one more bit keeps a - b on the grid of a and b
+        diff.encoder.nb_bit_precision += 1;
+        let min = f64::min(0., diff.encoder.o);
+        let max = f64::max(0., diff.encoder.o + diff.encoder.delta);
+        let delta = f64::max(max - min, b.encoder.delta);
+        // the final addition doubles delta, keep a granularity at most the one of a
+        let precision = f64::log2(2. * delta / a.encoder.get_granularity()).ceil().max(1.) as usize;
+        let mut encoder_output = a.encoder.clone();
+        encoder_output.o = min;
+        encoder_output.delta = delta;
+        encoder_output.nb_bit_precision = precision;
+        encoder_output.nb_bit_padding = 1;
+        encoder_output.round = false;
+        let mut res = LWE::gate(&diff, selector, bsk, ksk, &encoder_output)?;
+
+        // b must share the delta of the gated difference to be added to it
+        let mut encoder_b = encoder_output.clone();
+        encoder_b.o = b.encoder.o;
+        let b_refreshed = b.bootstrap_with_function(bsk, |x| x, &encoder_b).map_err(|e| e.to_string())?;
+        res.add_with_padding_inplace(&b_refreshed).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
+
+    /// Compute selector * ct with three bootstraps, for a selector encrypting 0 or 1:
+    /// ct is refreshed in an interval twice as large, the selector is mapped to 0 or k > delta,
+    /// so that the sum of the two tells both values apart and a last bootstrap outputs ct or 0
+    /// The sum is switched back to the input key of the bsk with ksk before the last bootstrap
+    pub(crate) fn gate(
+        ct: &concrete::LWE,
+        selector: &concrete::LWE,
+        bsk: &concrete::LWEBSK,
+        ksk: Option<&concrete::LWEKSK>,
+        encoder_output: &concrete::Encoder,
+    ) -> Result<concrete::LWE, String> {
+        let delta = ct.encoder.delta;
+        let k = 1.25 * delta;
+        let mut encoder_ct = ct.encoder.clone();
+        encoder_ct.delta = 2. * delta;
+        encoder_ct.nb_bit_precision += 2;
+        encoder_ct.nb_bit_padding = 2;
+        encoder_ct.round = false;
+        let mut sum = ct.bootstrap_with_function(bsk, |x| x, &encoder_ct).map_err(|e| e.to_string())?;
+        let mut encoder_selector = encoder_ct.clone();
+        encoder_selector.o = -delta / 4.;
+        let sel = selector.bootstrap_with_function(
+            bsk, |x| if x > 0.5 { k } else { 0. }, &encoder_selector).map_err(|e| e.to_string())?;
+        sum.add_with_padding_inplace(&sel).map_err(|e| e.to_string())?;
+        if let Some(ksk) = ksk {
+            sum = sum.keyswitch(ksk).map_err(|e| e.to_string())?;
+        }
+        // ct alone lies in [o, o + delta[ and ct + k in [o + k, o + k + delta[
+        let threshold = ct.encoder.o + (delta + k) / 2.;
+        sum.bootstrap_with_function(bsk, |x| if x >= threshold { x - k } else { 0. }, encoder_output)
+            .map_err(|e| e.to_string())
+    }
+
+    /// Bootstrap the sum of two encrypted booleans through `f` into a boolean with the
+    /// canonical encoder and the padding of self
+    fn bool_gate<F: Fn(f64) -> f64>(
+        &self, ct: &crate::LWE, bsk: &crate::LWEBSK, f: F,
+    ) -> PyResult<crate::LWE> {
+        let sum = translate_error!(self.data.add_with_padding(&ct.data))?;
+        let encoder_output = translate_error!(concrete::Encoder::new(
+            0., 1., 2, self.data.encoder.nb_bit_padding))?;
+        let data = translate_error!(sum.bootstrap_with_function(&bsk.data, f, &encoder_output))?;
+        Ok(LWE{ data })
+    }
+}
+
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<LWE>()?;
 
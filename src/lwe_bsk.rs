@@ -1,11 +1,22 @@
 use pyo3::prelude::*;
-use pyo3::exceptions::PyValueError;
-use pyo3::types::{PyFunction};
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::types::{PyAny, PyBytes, PyFunction, PyList};
 use concrete;
 use concrete::{Torus};
+use concrete_commons::parameters::{
+    DecompositionBaseLog, DecompositionLevelCount, GlweSize, LweDimension, PolynomialSize};
+use concrete_core::crypto::bootstrap::{Bootstrap, FourierBootstrapKey};
+use concrete_core::crypto::glwe::GlweCiphertext;
+use concrete_core::math::fft::Complex64;
+use concrete_core::math::tensor::{AsMutSlice, AsMutTensor, AsRefSlice, AsRefTensor};
+use concrete_npe as npe;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
 use super::{translate_error};//, LWESecretKey};
 
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, PartialEq, Clone)]
 pub struct LWEBSK {
     // pub ciphertexts: FourierBootstrapKey<AlignedVec<Complex64>,u64>,
@@ -15,6 +26,71 @@ pub struct LWEBSK {
     // pub base_log: usize,
     // pub level: usize,
     pub data: concrete::LWEBSK,
+    pub lut_cache: LutCache,
+}
+
+/// (o, delta, nb_bit_precision, nb_bit_padding, round) of an encoder, with the floats as bits
+type EncoderKey = (u64, u64, usize, usize, bool);
+/// (polynomial size, input encoder, output encoder) of a cached table
+type TableKey = (usize, EncoderKey, EncoderKey);
+
+/// Identity lookup tables already built for a bootstrapping key, keyed by the polynomial size
+/// and the (input, output) encoder pair, together with the number of lookups they served
+/// The cache is shared by the Rust clones of a key and does not take part in comparisons
+#[derive(Default, Clone)]
+pub struct LutCache {
+    tables: Arc<Mutex<HashMap<TableKey, Arc<Vec<Torus>>>>>,
+    hits: Arc<AtomicUsize>,
+}
+
+impl std::fmt::Debug for LutCache {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let (tables, hits) = self.stats();
+        write!(f, "LutCache {{ tables: {}, hits: {} }}", tables, hits)
+    }
+}
+
+impl PartialEq for LutCache {
+    fn eq(&self, _: &LutCache) -> bool {
+        true
+    }
+}
+
+impl LutCache {
+    fn encoder_key(encoder: &concrete::Encoder) -> EncoderKey {
+        (encoder.o.to_bits(), encoder.delta.to_bits(),
+         encoder.nb_bit_precision, encoder.nb_bit_padding, encoder.round)
+    }
+
+    /// Return the identity table of `bsk` for the encoder pair, building it on the first call
+    pub fn identity_table(
+        &self,
+        bsk: &concrete::LWEBSK,
+        encoder_input: &concrete::Encoder,
+        encoder_output: &concrete::Encoder,
+    ) -> Result<Arc<Vec<Torus>>, String> {
+        let key = (bsk.polynomial_size,
+                   LutCache::encoder_key(encoder_input), LutCache::encoder_key(encoder_output));
+        if let Some(table) = self.tables.lock().unwrap_or_else(|e| e.into_inner()).get(&key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return Ok(table.clone());
+        }
+        let table = Arc::new(bsk.generate_identity_look_up_table(encoder_input, encoder_output)
+            .map_err(|e| e.to_string())?);
+        self.tables.lock().unwrap_or_else(|e| e.into_inner()).insert(key, table.clone());
+        Ok(table)
+    }
+
+    /// Number of cached tables and number of lookups answered from the cache
+    pub fn stats(&self) -> (usize, usize) {
+        let tables = self.tables.lock().unwrap_or_else(|e| e.into_inner()).len();
+        (tables, self.hits.load(Ordering::Relaxed))
+    }
+
+    pub fn clear(&self) {
+        self.tables.lock().unwrap_or_else(|e| e.into_inner()).clear();
+        self.hits.store(0, Ordering::Relaxed);
+    }
 }
 
 #[pymethods]
@@ -75,6 +151,23 @@ impl LWEBSK {
         self.data.get_lwe_dimension()
     }
 
+    /// Check whether a ciphertext can be bootstrapped with this key
+    ///
+    /// # Argument
+    /// * `ct` - an LWE or a VectorLWE
+    ///
+    /// # Output
+    /// * true if the dimension of the ciphertext matches the input dimension of this key
+    pub fn is_compatible_with(&self, ct: &PyAny) -> PyResult<bool> {
+        if let Ok(ct) = ct.extract::<PyRef<crate::LWE>>() {
+            Ok(ct.data.dimension == self.data.get_lwe_dimension())
+        } else if let Ok(ct) = ct.extract::<PyRef<crate::VectorLWE>>() {
+            Ok(ct.data.dimension == self.data.get_lwe_dimension())
+        } else {
+            Err(PyTypeError::new_err("expected an LWE or a VectorLWE"))
+        }
+    }
+
     /// Return the log2 of the polynomial size of the RLWE involved in the bootstrap
     pub fn get_polynomial_size_log(&self) -> usize {
         self.data.get_polynomial_size_log()
@@ -110,6 +203,8 @@ impl LWEBSK {
     }
 
     /// Build a lookup table for the identity function from two encoders
+    /// The table is cached on the key, so that it is built only once per encoder pair
+    /// (LWE.bootstrap and VectorLWE.bootstrap_nth use the same cache)
     ///
     /// # Argument
     /// * `encoder_input` - the encoder of the input (of the bootstrap)
@@ -122,8 +217,73 @@ impl LWEBSK {
         encoder_input: &crate::Encoder,
         encoder_output: &crate::Encoder,
     ) -> PyResult<Vec<Torus>> {
-        translate_error!(self.data.generate_identity_look_up_table(
-            &encoder_input.data, &encoder_output.data))
+        let table = translate_error!(self.lut_cache.identity_table(
+            &self.data, &encoder_input.data, &encoder_output.data))?;
+        Ok(table.to_vec())
+    }
+
+    /// Return the state of the cache of identity lookup tables
+    ///
+    /// # Output
+    /// * a tuple (number of cached tables, number of lookups answered from the cache)
+    pub fn lut_cache_info(&self) -> (usize, usize) {
+        self.lut_cache.stats()
+    }
+
+    /// Drop all the cached identity lookup tables and reset the hit counter
+    pub fn clear_lut_cache(&mut self) {
+        self.lut_cache.clear();
+    }
+
+    /// Build the lookup tables of several functions of the same input, to be used with
+    /// LWE.bootstrap_multivalue on ciphertexts encoded with `encoder_input`
+    /// The functions are called here only, so the tables can be reused for any number of
+    /// ciphertexts; each table is an ordinary functional lookup table, so every function keeps
+    /// the precision of the input. concrete-core extracts a single value per bootstrap, hence
+    /// bootstrap_multivalue costs one bootstrap per function
+    ///
+    /// # Argument
+    /// * `encoder_input` - the encoder of the input (of the bootstrap)
+    /// * `encoders_output` - the encoders of the outputs, one for each function
+    /// * `funcs` - the functions
+    ///
+    /// # Output
+    /// * a MultivalueLUT to pass to LWE.bootstrap_multivalue
+    /// * ValueError - if the lists are empty or of different lengths, or the input encoder has no
+    ///   precision or no padding
+    pub fn generate_multivalue_look_up_table(
+        &self,
+        encoder_input: &crate::Encoder,
+        encoders_output: &PyList,
+        funcs: &PyList,
+    ) -> PyResult<crate::MultivalueLUT> {
+        if funcs.is_empty() || funcs.len() != encoders_output.len() {
+            return Err(PyValueError::new_err(format!(
+                "expected as many output encoders as functions (at least 1), got {} and {}",
+                encoders_output.len(), funcs.len())));
+        }
+        let encoders: Vec<crate::Encoder> = encoders_output.extract()?;
+        let funcs: Vec<&PyFunction> = funcs.extract()?;
+        let mut tables = Vec::with_capacity(funcs.len());
+        for (f, encoder_output) in funcs.iter().zip(encoders.iter()) {
+            // an error of the function is kept aside and raised once the table is built
+            let error = RefCell::new(None);
+            let fun = |x: f64| f.call1((x,)).and_then(|y| y.extract::<f64>()).unwrap_or_else(|e| {
+                error.borrow_mut().get_or_insert(e);
+                0.
+            });
+            let table = translate_error!(self.data.generate_functional_look_up_table(
+                &encoder_input.data, &encoder_output.data, fun))?;
+            if let Some(e) = error.into_inner() {
+                return Err(e);
+            }
+            tables.push(table);
+        }
+        Ok(crate::MultivalueLUT {
+            tables,
+            encoder_input: encoder_input.data.clone(),
+            encoders_output: encoders.into_iter().map(|e| e.data).collect(),
+        })
     }
 
     /// Create a valid bootstrapping key
@@ -144,7 +304,7 @@ impl LWEBSK {
         level: usize,
     ) -> LWEBSK {
         let data = concrete::LWEBSK::new(&sk_input.data, &sk_output.data, base_log, level);
-        LWEBSK{ data }
+        LWEBSK{ data, lut_cache: LutCache::default() }
     }
 
     /// Create an empty bootstrapping key
@@ -165,22 +325,287 @@ impl LWEBSK {
         level: usize,
     ) -> LWEBSK {
         let data = concrete::LWEBSK::zero(&sk_input.data, &sk_output.data, base_log, level);
-        LWEBSK{ data }
+        LWEBSK{ data, lut_cache: LutCache::default() }
     }
 
-    pub fn save(&self, path: &str) {
-        self.data.save(path);
+    /// Check that a bootstrapping key can be built from these parameters and can bootstrap at
+    /// least 1 bit of message (with 1 bit of padding), without generating anything
+    /// The checks are: non-zero dimensions, a polynomial size that is a power of 2, a decomposition
+    /// that fits in a Torus element, a rounding drift of the input that leaves room in the lookup
+    /// table, and an output noise (predicted with the npe crate) that leaves room for the message
+    ///
+    /// # Argument
+    /// * `lwe_input` - the parameters of the LWE secret key (input for the bootstrap)
+    /// * `rlwe_output` - the parameters of the RLWE secret key (output for the bootstrap)
+    /// * `base_log` - the log2 of the decomposition base
+    /// * `level` - the number of levels of the decomposition
+    ///
+    /// # Output
+    /// * ValueError - listing all the problems found
+    #[staticmethod]
+    pub fn check_params(
+        lwe_input: &crate::LWEParams,
+        rlwe_output: &crate::RLWEParams,
+        base_log: usize,
+        level: usize,
+    ) -> PyResult<()> {
+        let (n, k, size) = (
+            lwe_input.data.dimension, rlwe_output.data.dimension, rlwe_output.data.polynomial_size);
+        let mut problems = Vec::new();
+        if n == 0 {
+            problems.push("the LWE dimension is 0".to_string());
+        }
+        if k == 0 {
+            problems.push("the RLWE dimension is 0".to_string());
+        }
+        if size < 2 || !size.is_power_of_two() {
+            problems.push(format!("the polynomial size {} is not a power of 2 larger than 1", size));
+        }
+        if base_log == 0 || level == 0 {
+            problems.push(format!("base_log ({}) and level ({}) must be at least 1", base_log, level));
+        } else if base_log * level > Torus::BITS as usize {
+            problems.push(format!(
+                "the decomposition keeps base_log * level = {} bits but a Torus element has {}",
+                base_log * level, Torus::BITS));
+        }
+        if problems.is_empty() {
+            // 1 bit of message and 1 bit of padding have to fit above the drift of the modulus switch
+            let size_log = size.trailing_zeros() as usize;
+            let drift = npe::lwe::log2_rounding_noise(n).ceil() as usize + 1;
+            if 2 + drift > size_log {
+                problems.push(format!(
+                    "the rounding drift of an LWE of dimension {} takes {} of the {} bits of the lookup table",
+                    n, drift, size_log));
+            }
+            let variance = <Torus as npe::cross::Cross>::bootstrap(
+                n, k, level, base_log, size, f64::powi(2., 2 * rlwe_output.data.log2_std_dev));
+            // the 3 sigma bound of the output noise must stay below half a box of 1 bit + 1 bit of padding
+            let log2_bound = (3. * variance.sqrt()).log2();
+            if log2_bound > -3. {
+                problems.push(format!(
+                    "the output noise of the bootstrap reaches 2^{:.1} of the torus, above the 2^-3 needed for 1 bit",
+                    log2_bound));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(PyValueError::new_err(format!(
+                "incompatible bootstrapping parameters (LWE dimension {}, RLWE dimension {}, polynomial size {}, base_log {}, level {}): {}",
+                n, k, size, base_log, level, problems.join("; "))))
+        }
     }
 
+    /// Export the Fourier coefficients of the key as raw bytes
+    /// (the real and imaginary parts of each coefficient as little-endian f64)
+    ///
+    /// # Output
+    /// * a bytes object that from_raw accepts together with the parameters of this key
+    pub fn to_raw<'p>(&self, py: Python<'p>) -> &'p PyBytes {
+        let coefficients = self.data.ciphertexts.as_tensor().as_slice();
+        let mut res = Vec::with_capacity(coefficients.len() * 16);
+        for c in coefficients.iter() {
+            res.extend_from_slice(&c.re.to_le_bytes());
+            res.extend_from_slice(&c.im.to_le_bytes());
+        }
+        PyBytes::new(py, &res)
+    }
+
+    /// Rebuild a bootstrapping key from its raw Fourier coefficients (see to_raw)
+    /// The LWE dimension of the input is deduced from the length of `data`
+    ///
+    /// # Argument
+    /// * `dimension` - the dimension of the RLWE secret key (output for the bootstrap)
+    /// * `polynomial_size` - the polynomial size of the RLWE secret key, a power of 2
+    /// * `base_log` - the log2 of the decomposition base
+    /// * `level` - the number of levels of the decomposition
+    /// * `variance` - the variance of the noise of the key
+    /// * `data` - the raw Fourier coefficients
+    ///
+    /// # Output
+    /// * an LWEBSK
+    /// * ValueError if the length of `data` does not match the parameters
+    /// * ValueError if the parameters describe a key too large to be addressed
     #[staticmethod]
-    pub fn load(path: &str) -> crate::LWEBSK {
-        let data = concrete::LWEBSK::load(path);
-        LWEBSK{ data }
+    pub fn from_raw(
+        dimension: usize,
+        polynomial_size: usize,
+        base_log: usize,
+        level: usize,
+        variance: f64,
+        data: &[u8],
+    ) -> PyResult<LWEBSK> {
+        if !polynomial_size.is_power_of_two() {
+            return Err(PyValueError::new_err(format!(
+                "polynomial size must be a power of 2, got {}", polynomial_size)));
+        }
+        let block = dimension.checked_add(1)
+            .and_then(|glwe_size| glwe_size.checked_mul(glwe_size))
+            .and_then(|x| x.checked_mul(level))
+            .and_then(|x| x.checked_mul(polynomial_size))
+            .and_then(|x| x.checked_mul(16))
+            .ok_or_else(|| PyValueError::new_err(format!(
+                "a key with dimension {}, polynomial size {} and level {} is too large",
+                dimension, polynomial_size, level)))?;
+        if block == 0 || data.is_empty() || !data.len().is_multiple_of(block) {
+            return Err(PyValueError::new_err(format!(
+                "{} bytes do not match a key with blocks of {} bytes", data.len(), block)));
+        }
+        let mut ciphertexts = FourierBootstrapKey::allocate(
+            Complex64::new(0., 0.),
+            GlweSize(dimension + 1),
+            PolynomialSize(polynomial_size),
+            DecompositionLevelCount(level),
+            DecompositionBaseLog(base_log),
+            LweDimension(data.len() / block),
+        );
+        for (c, bytes) in ciphertexts.as_mut_tensor().as_mut_slice().iter_mut()
+            .zip(data.chunks_exact(16))
+        {
+            c.re = f64::from_le_bytes(bytes[..8].try_into().unwrap());
+            c.im = f64::from_le_bytes(bytes[8..].try_into().unwrap());
+        }
+        let data = concrete::LWEBSK {
+            ciphertexts, variance, dimension, polynomial_size, base_log, level,
+        };
+        Ok(LWEBSK{ data, lut_cache: LutCache::default() })
     }
 
-    pub fn __repr__(&self) -> String {
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        crate::helper_catch_panic(|| self.data.save(path))
+    }
+
+    #[staticmethod]
+    pub fn load(path: &str) -> PyResult<LWEBSK> {
+        let data = crate::helper_catch_panic(|| concrete::LWEBSK::load(path))?;
+        Ok(LWEBSK{ data, lut_cache: LutCache::default() })
+    }
+
+    /// Load a key saved with save by memory-mapping the file (read-only)
+    ///
+    /// The file pages live in the OS page cache shared by all the processes loading the same key,
+    /// and the coefficients are decoded directly from the mapping, so the peak memory is one key
+    /// instead of two with load.
+    /// Note that the Fourier coefficients still have to be copied into an aligned buffer owned
+    /// by the key, as required by the FFT.
+    /// The file must not be truncated or rewritten during the load, which would crash the process
+    /// with SIGBUS or load a corrupted key; the mapping is released once the key is decoded.
+    /// If the file cannot be mapped, a RuntimeWarning is emitted and the key is loaded with load.
+    /// # Argument
+    /// * `path` - the path of a file written by LWEBSK.save
+    /// # Output
+    /// * the loaded LWEBSK
+    /// * ValueError - if the file is not a valid key
+    #[staticmethod]
+    pub fn load_mmap(py: Python, path: &str) -> PyResult<LWEBSK> {
+        let file = translate_error!(std::fs::File::open(path))?;
+        match crate::helper_mmap(&file) {
+            Ok(map) => {
+                let data = translate_error!(LWEBSK::from_saved_bytes(map.as_slice()))?;
+                Ok(LWEBSK{ data, lut_cache: LutCache::default() })
+            }
+            Err(e) => {
+                let warning = py.import("builtins")?.getattr("RuntimeWarning")?;
+                PyErr::warn(py, warning, &format!(
+                    "cannot memory-map {} ({}), falling back to a normal load", path, e), 1)?;
+                LWEBSK::load(path)
+            }
+        }
+    }
+
+    /// Return the full description of the key, including all its coefficients
+    pub fn describe(&self) -> String {
         self.data.to_string()
     }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<LWEBSK, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<LWEBSK> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(LWEBSK{ data, lut_cache: LutCache::default() })
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "LWEBSK(lwe_dimension={}, dimension={}, polynomial_size={}, base_log={}, level={}, variance={:e})",
+            self.data.get_lwe_dimension(), self.data.dimension, self.data.polynomial_size,
+            self.data.base_log, self.data.level, self.data.variance)
+    }
+}
+
+impl LWEBSK {
+    /// Decode the content of a file written by concrete::LWEBSK::save, i.e. a bincode serialized
+    /// tensor of u64: its length, the 6 parameters and the (re, im) bits of each coefficient
+    fn from_saved_bytes(bytes: &[u8]) -> Result<concrete::LWEBSK, String> {
+        let word = |i: usize| -> u64 {
+            u64::from_le_bytes(bytes[8 * i..8 * (i + 1)].try_into().unwrap())
+        };
+        if bytes.len() < 8 * 7 || !bytes.len().is_multiple_of(8) || word(0) as usize != bytes.len() / 8 - 1 {
+            return Err(format!("{} bytes do not form a serialized bootstrapping key", bytes.len()));
+        }
+        let (dimension, polynomial_size) = (word(2) as usize, word(3) as usize);
+        let (base_log, level, key_size) = (word(4) as usize, word(5) as usize, word(6) as usize);
+        // the header is untrusted, an overflowing size cannot match the file either
+        let nb_words = dimension.checked_add(1)
+            .and_then(|glwe_size| glwe_size.checked_mul(glwe_size))
+            .and_then(|x| x.checked_mul(level))
+            .and_then(|x| x.checked_mul(polynomial_size))
+            .and_then(|x| x.checked_mul(key_size))
+            .and_then(|x| x.checked_mul(2));
+        if !polynomial_size.is_power_of_two() || nb_words != Some(bytes.len() / 8 - 7) {
+            return Err(format!(
+                "the size of the file does not match a key of dimension {}, polynomial size {}, level {} and key size {}",
+                dimension, polynomial_size, level, key_size));
+        }
+        let mut ciphertexts = FourierBootstrapKey::allocate(
+            Complex64::new(0., 0.),
+            GlweSize(dimension + 1),
+            PolynomialSize(polynomial_size),
+            DecompositionLevelCount(level),
+            DecompositionBaseLog(base_log),
+            LweDimension(key_size),
+        );
+        for (i, c) in ciphertexts.as_mut_tensor().as_mut_slice().iter_mut().enumerate() {
+            c.re = f64::from_bits(word(7 + 2 * i));
+            c.im = f64::from_bits(word(8 + 2 * i));
+        }
+        Ok(concrete::LWEBSK {
+            ciphertexts, variance: f64::from_bits(word(1)), dimension, polynomial_size, base_log, level,
+        })
+    }
+
+    /// Bootstrap `ct` with a lookup table generated beforehand for its encoder, so that
+    /// the table can be shared between ciphertexts (and threads) without calling the function again
+    pub(crate) fn bootstrap_with_table(
+        bsk: &concrete::LWEBSK, ct: &concrete::LWE, table: &[Torus], encoder_output: &concrete::Encoder,
+    ) -> Result<concrete::LWE, String> {
+        if ct.dimension != bsk.get_lwe_dimension() {
+            return Err(format!(
+                "DimensionError: the ciphertext has dimension {} but the bootstrapping key expects {}",
+                ct.dimension, bsk.get_lwe_dimension()));
+        }
+        if ct.encoder.nb_bit_padding == 0 {
+            return Err("the ciphertext needs at least 1 bit of padding".to_string());
+        }
+        let mut accumulator = GlweCiphertext::allocate(
+            0, PolynomialSize(bsk.polynomial_size), GlweSize(bsk.dimension + 1));
+        accumulator.get_mut_body().as_mut_tensor().as_mut_slice().copy_from_slice(table);
+        let mut input = ct.clone();
+        input.remove_padding_inplace(ct.encoder.nb_bit_padding - 1).map_err(|e| e.to_string())?;
+
+        let mut res = concrete::LWE::zero(bsk.dimension * bsk.polynomial_size).map_err(|e| e.to_string())?;
+        bsk.ciphertexts.bootstrap(&mut res.ciphertext, &input.ciphertext, &accumulator);
+        res.variance = <Torus as npe::cross::Cross>::bootstrap(
+            ct.dimension, bsk.dimension, bsk.level, bsk.base_log, bsk.polynomial_size, bsk.variance);
+        res.encoder = encoder_output.clone();
+        res.encoder.update_precision_from_variance(res.variance).map_err(|e| e.to_string())?;
+        Ok(res)
+    }
 }
 
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -1,8 +1,9 @@
 use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
 use concrete;
 // use super::{LWESecretKey};
 
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, PartialEq)]
 pub struct LWEKSK {
     // pub ciphertexts: crypto::lwe::LweKeyswitchKey<Vec<Torus>>,
@@ -16,6 +17,32 @@ pub struct LWEKSK {
 
 #[pymethods]
 impl LWEKSK {
+
+    #[getter]
+    pub fn get_base_log(&self) -> usize {
+        self.data.base_log
+    }
+
+    #[getter]
+    pub fn get_level(&self) -> usize {
+        self.data.level
+    }
+
+    #[getter]
+    pub fn get_dimension_before(&self) -> usize {
+        self.data.dimension_before
+    }
+
+    #[getter]
+    pub fn get_dimension_after(&self) -> usize {
+        self.data.dimension_after
+    }
+
+    #[getter]
+    pub fn get_variance(&self) -> f64 {
+        self.data.variance
+    }
+
     /// Generate an empty LWE key switching key
     ///
     /// # Argument
@@ -57,19 +84,71 @@ impl LWEKSK {
         LWEKSK{ data }
     }
 
-    pub fn save(&self, path: &str) {
-        self.data.save(path);
+    /// Generate a key switching key from a large LWE secret key to the input key of a
+    /// bootstrapping key, so that ciphertexts can be key switched right before a bootstrap
+    /// The bootstrapping key does not hold its secret keys, so the input secret key of the
+    /// bootstrap has to be given as well
+    ///
+    /// # Argument
+    /// * `sk_large` - an LWE secret key (input for the key switch)
+    /// * `sk_bootstrap` - the LWE secret key used as input when `bsk` was generated
+    /// * `bsk` - the bootstrapping key
+    /// * `base_log` - the log2 of the decomposition base
+    /// * `level` - the number of levels of the decomposition
+    ///
+    /// # Output
+    /// * an LWEKSK
+    /// * ValueError if the dimension of `sk_bootstrap` does not match `bsk`
+    #[staticmethod]
+    pub fn for_bootstrap(
+        sk_large: &crate::LWESecretKey,
+        sk_bootstrap: &crate::LWESecretKey,
+        bsk: &crate::LWEBSK,
+        base_log: usize,
+        level: usize,
+    ) -> PyResult<LWEKSK> {
+        if sk_bootstrap.data.dimension != bsk.data.get_lwe_dimension() {
+            return Err(PyValueError::new_err(format!(
+                "the secret key has dimension {} but the bsk expects {}",
+                sk_bootstrap.data.dimension, bsk.data.get_lwe_dimension())));
+        }
+        let data = concrete::LWEKSK::new(&sk_large.data, &sk_bootstrap.data, base_log, level);
+        Ok(LWEKSK{ data })
+    }
+
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        crate::helper_catch_panic(|| self.data.save(path))
     }
 
     #[staticmethod]
-    pub fn load(path: &str) -> crate::LWEKSK {
-        let data = concrete::LWEKSK::load(path);
-        LWEKSK{ data }
+    pub fn load(path: &str) -> PyResult<LWEKSK> {
+        let data = crate::helper_catch_panic(|| concrete::LWEKSK::load(path))?;
+        Ok(LWEKSK{ data })
     }
 
-    pub fn __repr__(&self) -> String {
+    /// Return the full description of the key, including all its coefficients
+    pub fn describe(&self) -> String {
         self.data.to_string()
     }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<LWEKSK, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<LWEKSK> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(LWEKSK{ data })
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "LWEKSK(dimension_before={}, dimension_after={}, base_log={}, level={}, variance={:e})",
+            self.data.dimension_before, self.data.dimension_after,
+            self.data.base_log, self.data.level, self.data.variance)
+    }
     
 }
 
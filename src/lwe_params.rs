@@ -3,7 +3,7 @@ use pyo3::exceptions::*;
 use concrete;
 use super::translate_error;
 
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct LWEParams {
     // #[pyo3(get, set)]
@@ -203,9 +203,49 @@ impl LWEParams {
         f64::powi(2., self.data.log2_std_dev)
     }
 
+    /// Pick the smallest preset which leaves room for the requested precision and
+    /// one bit of padding per planned bootstrap above the noise of a fresh ciphertext
+    /// (bound at 99%), at the given level of security
+    /// # Arguments
+    /// * `nb_bit_precision` - the number of bits of precision of the messages
+    /// * `nb_bootstraps` - the number of bootstraps planned on a ciphertext
+    /// * `security_bits` - the level of security, 80 or 128
+    /// # Output
+    /// * a preset LWEParams
+    /// * ValueError - if the security level is unknown or no preset is large enough
+    #[staticmethod]
+    pub fn recommend(
+        nb_bit_precision: usize, nb_bootstraps: usize, security_bits: usize,
+    ) -> PyResult<LWEParams> {
+        let presets = match security_bits {
+            80 => vec![
+                concrete::LWE80_256, concrete::LWE80_512, concrete::LWE80_630, concrete::LWE80_650,
+                concrete::LWE80_688, concrete::LWE80_710, concrete::LWE80_750, concrete::LWE80_800,
+                concrete::LWE80_830, concrete::LWE80_1024],
+            128 => vec![
+                concrete::LWE128_256, concrete::LWE128_512, concrete::LWE128_630, concrete::LWE128_650,
+                concrete::LWE128_688, concrete::LWE128_710, concrete::LWE128_750, concrete::LWE128_800,
+                concrete::LWE128_830, concrete::LWE128_1024, concrete::LWE128_2048],
+            _ => return Err(PyValueError::new_err(format!(
+                "no preset for {} bits of security, use 80 or 128", security_bits))),
+        };
+        let torus_bit = <concrete::Torus as concrete_commons::numeric::Numeric>::BITS;
+        let needed = nb_bit_precision + nb_bootstraps;
+        let found = presets.into_iter().find(|p| {
+            let noise = concrete_npe::nb_bit_from_variance_99(
+                f64::powi(2., 2 * p.log2_std_dev), torus_bit);
+            noise + needed <= torus_bit
+        });
+        match found {
+            Some(data) => Ok(LWEParams{ data }),
+            None => Err(PyValueError::new_err(format!(
+                "no preset at {} bits of security supports {} bit(s) of precision with {} bootstrap(s), \
+                consider a lower precision", security_bits, nb_bit_precision, nb_bootstraps))),
+        }
+    }
+
     pub fn save(&self, path: &str) -> PyResult<()> {
-        self.data.save(path).expect("Failed in saving LWE paramter");
-        Ok(())
+        translate_error!(self.data.save(path))
     }
 
     #[staticmethod]
@@ -214,6 +254,18 @@ impl LWEParams {
         Ok(LWEParams{ data })
     }
 
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<LWEParams, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<LWEParams> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(LWEParams{ data })
+    }
+
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
@@ -1,9 +1,11 @@
 use pyo3::prelude::*;
 use pyo3::exceptions::*;
 use concrete;
+use concrete_core::math::tensor::{AsRefSlice, AsRefTensor};
+use sha2::{Digest, Sha256};
 use super::{translate_error, RLWESecretKey};
 
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, PartialEq, Clone)]
 pub struct LWESecretKey {
     // pub val: LweSecretKey<BinaryKeyKind, Vec<u64>>,
@@ -77,19 +79,73 @@ impl LWESecretKey {
         f64::powi(self.data.std_dev, 2i32)
     }
 
+    /// Generate a pool of fresh encryptions of zero, to be added to ciphertexts to re-randomize
+    /// them (e.g. with add_with_new_min and the min of their encoders)
+    /// The zeros are encoded with the delta, precision and padding of `encoder` but with a min of 0,
+    /// so that adding them does not change the decrypted values
+    /// This requires the secret key, so it is meant to be run on the client side
+    /// # Input
+    /// * `encoder` - the encoder of the ciphertexts to re-randomize
+    /// * `n` - the number of encryptions of zero, at least 1
+    /// # Output
+    /// * a VectorLWE containing `n` encryptions of zero
+    pub fn generate_zero_pool(
+        &self,
+        encoder: &crate::Encoder,
+        n: usize,
+    ) -> PyResult<crate::VectorLWE> {
+        let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, n))?;
+        translate_error!(data.encrypt_raw(&self.data, &vec![0; n]))?;
+        let mut zero_encoder = encoder.data.clone();
+        zero_encoder.o = 0.;
+        data.encoders = vec![zero_encoder; n];
+        Ok(crate::VectorLWE{ data, key_fingerprint: Some(LWESecretKey::fingerprint_of(&self.data)), shape: None })
+    }
+
     pub fn save(&self, path: &str) -> PyResult<()> {
         translate_error!(self.data.save(path))
     }
 
     #[staticmethod]
     pub fn load(path: &str) -> PyResult<LWESecretKey> {
-        let data = concrete::LWESecretKey::load(path).unwrap();
+        let data = translate_error!(concrete::LWESecretKey::load(path))?;
+        Ok(LWESecretKey{ data })
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<LWESecretKey, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<LWESecretKey> {
+        let data = crate::helper_from_bytes(bytes)?;
         Ok(LWESecretKey{ data })
     }
 
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
+
+    /// Return a fingerprint of the key (the first 8 bytes of the SHA-256 of its coefficients,
+    /// in hexadecimal), which identifies the key without revealing it
+    pub fn fingerprint(&self) -> String {
+        format!("{:016x}", LWESecretKey::fingerprint_of(&self.data))
+    }
+}
+
+impl LWESecretKey {
+    /// Fingerprint of a secret key, stored by the ciphertexts encrypted under it
+    pub(crate) fn fingerprint_of(data: &concrete::LWESecretKey) -> u64 {
+        let mut bytes = b"pyconcrete.LWESecretKey".to_vec();
+        bytes.extend_from_slice(&(data.dimension as u64).to_le_bytes());
+        for s in data.val.as_tensor().as_slice() {
+            bytes.extend_from_slice(&s.to_le_bytes());
+        }
+        let digest = Sha256::digest(&bytes);
+        u64::from_be_bytes(digest[..8].try_into().unwrap())
+    }
 }
 
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
@@ -0,0 +1,65 @@
+//! multivalue lookup table module
+use pyo3::prelude::*;
+use concrete;
+use concrete::Torus;
+
+/// Lookup tables of several functions of the same input, built by
+/// LWEBSK.generate_multivalue_look_up_table and used by LWE.bootstrap_multivalue
+///
+/// # Attributes
+/// * `tables` - the lookup table of each function
+/// * `encoder_input` - the encoder of the ciphertexts the tables were built for
+/// * `encoders_output` - the encoder of the output of each function
+#[pyclass(module = "pyconcrete")]
+#[derive(Debug, PartialEq, Clone)]
+pub struct MultivalueLUT {
+    pub tables: Vec<Vec<Torus>>,
+    pub encoder_input: concrete::Encoder,
+    pub encoders_output: Vec<concrete::Encoder>,
+}
+
+#[pymethods]
+impl MultivalueLUT {
+    #[getter]
+    pub fn get_encoder_input(&self) -> crate::Encoder {
+        crate::Encoder{ data: self.encoder_input.clone() }
+    }
+
+    #[getter]
+    pub fn get_encoders_output(&self) -> Vec<crate::Encoder> {
+        self.encoders_output.iter().map(|e| crate::Encoder{ data: e.clone() }).collect()
+    }
+
+    #[getter]
+    pub fn get_nb_functions(&self) -> usize {
+        self.tables.len()
+    }
+
+    #[getter]
+    pub fn get_polynomial_size(&self) -> usize {
+        self.tables[0].len()
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!("MultivalueLUT(nb_functions={}, polynomial_size={}, encoder_input={})",
+                self.tables.len(), self.tables[0].len(), self.encoder_input)
+    }
+}
+
+impl MultivalueLUT {
+    /// Whether the tables decode the messages of `encoder` correctly: same interval and precision
+    /// (the padding does not matter, the bootstrap keeps a single bit of it)
+    pub(crate) fn applies_to(&self, encoder: &concrete::Encoder) -> bool {
+        let input = &self.encoder_input;
+        input.nb_bit_precision == encoder.nb_bit_precision
+            && input.round == encoder.round
+            && crate::helper_deltas_eq(input.delta, encoder.delta)
+            && (input.o - encoder.o).abs() <= 1e-10 * input.delta.abs()
+    }
+}
+
+pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<MultivalueLUT>()?;
+
+    Ok(())
+}
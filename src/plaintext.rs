@@ -1,6 +1,6 @@
 use pyo3::prelude::*;
 use pyo3::exceptions::*;
-use pyo3::types::PyList;
+use pyo3::types::{PyFunction, PyList};
 use concrete;
 use concrete::Torus;
 use super::translate_error;
@@ -10,7 +10,7 @@ use super::translate_error;
 /// * `encoder` - the list of the encoders (one for each plaintext)
 /// * `plaintexts` - the list of plaintexts
 /// * `nb_plaintexts` - the size of both lists
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct Plaintext {
     // pub encoders: Vec<crate::Encoder>,
@@ -73,6 +73,26 @@ impl Plaintext {
         }
     }
 
+    /// Instantiate a new Plaintext (set to zero) with the given encoders attached
+    /// # Argument
+    /// * `encoders` - a list of Encoder elements, one for each plaintext
+    /// # Output
+    /// * a new Plaintext of the size of `encoders` ready for encode_inplace
+    #[staticmethod]
+    pub fn zeros_with_encoders(encoders: &PyList) -> PyResult<Plaintext> {
+        let encoders = encoders.iter().map(
+            |t| Ok(t.extract::<crate::Encoder>()?.data)
+        ).collect::<PyResult<Vec<concrete::Encoder>>>()?;
+        let nb_plaintexts = encoders.len();
+        Ok(Plaintext {
+            data: concrete::Plaintext{
+                encoders,
+                plaintexts: vec![0; nb_plaintexts],
+                nb_plaintexts,
+            }
+        })
+    }
+
     /// Instantiate a new Plaintext filled with plaintexts
     /// # Argument
     /// * `messages`- a list of messages as u64
@@ -175,6 +195,72 @@ impl Plaintext {
         translate_error!(self.data.decode())
     }
 
+    /// Decode every plaintext, apply a function to the messages and re-encode them
+    /// with their own encoders
+    /// # Argument
+    /// * `f` - a Python function taking and returning a float
+    /// # Output
+    /// * a new Plaintext with the same encoders
+    /// * MessageOutsideIntervalError - if a result is outside the interval of its encoder
+    pub fn map(&self, f: &PyFunction) -> PyResult<Plaintext> {
+        let messages = translate_error!(self.data.decode())?;
+        let mut mapped = Vec::with_capacity(messages.len());
+        for m in messages.iter() {
+            mapped.push(f.call1((*m,))?.extract::<f64>()?);
+        }
+        let mut res = self.clone();
+        translate_error!(res.data.encode_inplace(&mapped))?;
+        Ok(res)
+    }
+
+    /// Compare the decoded messages of two plaintexts instead of their raw encodings
+    /// # Argument
+    /// * `other` - the plaintext to compare with
+    /// * `tol` - the largest absolute difference allowed between two messages
+    /// # Output
+    /// * true if both have the same number of plaintexts and every pair of messages is within `tol`
+    pub fn approx_eq(&self, other: &Plaintext, tol: f64) -> PyResult<bool> {
+        if tol < 0. || tol.is_nan() {
+            return Err(PyValueError::new_err(format!("the tolerance must be non-negative, got {}", tol)));
+        }
+        if self.data.nb_plaintexts != other.data.nb_plaintexts {
+            return Ok(false);
+        }
+        let left = translate_error!(self.data.decode())?;
+        let right = translate_error!(other.data.decode())?;
+        Ok(left.iter().zip(right.iter()).all(|(a, b)| (a - b).abs() <= tol))
+    }
+
+    /// Append encodings of the message 0 until the plaintext holds `length` plaintexts
+    /// # Argument
+    /// * `length` - the number of plaintexts of the output
+    /// * `encoder` - the encoder of the appended plaintexts
+    /// # Output
+    /// * a new Plaintext whose first plaintexts are the ones of self
+    /// * ValueError - if self already holds more than `length` plaintexts
+    /// * MessageOutsideIntervalError - if 0 is not in the interval of `encoder`
+    pub fn pad_to(&self, length: usize, encoder: &crate::Encoder) -> PyResult<Plaintext> {
+        if self.data.nb_plaintexts > length {
+            return Err(PyValueError::new_err(format!(
+                "cannot pad {} plaintexts to {}", self.data.nb_plaintexts, length)));
+        }
+        let zero = translate_error!(encoder.data.encode_core(0.))?;
+        let mut res = self.clone();
+        res.data.plaintexts.resize(length, zero);
+        res.data.encoders.resize(length, encoder.data.clone());
+        res.data.nb_plaintexts = length;
+        Ok(res)
+    }
+
+    /// Encrypt the plaintexts into a VectorLWE (same as VectorLWE.encrypt)
+    /// # Argument
+    /// * `sk` - an LWE secret key
+    /// # Output
+    /// * a new VectorLWE
+    pub fn encrypt(&self, sk: &crate::LWESecretKey) -> PyResult<crate::VectorLWE> {
+        crate::VectorLWE::encrypt(sk, self)
+    }
+
     /// Set the encoder list of this instance from an input list of encoders
     /// # Argument
     /// * `encoders` - a list of Encoder elements
@@ -241,7 +327,19 @@ impl Plaintext {
 
     #[staticmethod]
     pub fn load(path: &str) -> PyResult<Plaintext> {
-        let data = concrete::Plaintext::load(path).unwrap();
+        let data = translate_error!(concrete::Plaintext::load(path))?;
+        Ok(Plaintext{ data })
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<Plaintext, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<Plaintext> {
+        let data = crate::helper_from_bytes(bytes)?;
         Ok(Plaintext{ data })
     }
 
@@ -0,0 +1,212 @@
+//! rlwe packing key switching key module
+use pyo3::prelude::*;
+use pyo3::exceptions::PyValueError;
+use concrete;
+use concrete::Torus;
+use concrete_commons::parameters::{DecompositionBaseLog, DecompositionLevelCount};
+use concrete_core::math::decomposition::SignedDecomposer;
+use concrete_core::math::tensor::{AsMutSlice, AsMutTensor, AsRefSlice, AsRefTensor};
+use concrete_npe as npe;
+use serde::{Deserialize, Serialize};
+
+use super::translate_error;
+
+/// Structure containing a packing key switching key, which moves LWE ciphertexts
+/// into the slots of an RLWE ciphertext
+///
+/// # Attributes
+/// * `ciphertexts` - the RLWE encryptions of each coefficient of the LWE secret key, for each level
+/// * `base_log` - the log2 of the decomposition base
+/// * `level` - the number of levels of the decomposition
+/// * `dimension_before` - the size of the LWE mask of the input ciphertexts
+/// * `variance` - the variance of the noise of the key
+#[pyclass(module = "pyconcrete")]
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct RLWEKSK {
+    pub ciphertexts: concrete::VectorRLWE,
+    pub base_log: usize,
+    pub level: usize,
+    pub dimension_before: usize,
+    pub variance: f64,
+}
+
+#[pymethods]
+impl RLWEKSK {
+    /// Check that the deserialized fields describe a key pack can use without going out of bounds
+    fn check_shape(&self) -> PyResult<()> {
+        let bits = <Torus as concrete_commons::numeric::Numeric>::BITS;
+        let rlwe = &self.ciphertexts;
+        let expected = self.dimension_before.checked_mul(self.level)
+            .and_then(|n| n.checked_mul(rlwe.dimension + 1))
+            .and_then(|n| n.checked_mul(rlwe.polynomial_size));
+        if self.base_log == 0 || self.level == 0 || self.base_log.saturating_mul(self.level) > bits
+            || expected != Some(rlwe.ciphertexts.as_tensor().len())
+            || rlwe.nb_ciphertexts != self.dimension_before * self.level {
+            return Err(PyValueError::new_err("the serialized RLWEKSK is corrupted"));
+        }
+        Ok(())
+    }
+
+    /// Generate a valid packing key switching key
+    /// # Argument
+    /// * `sk_before` - an LWE secret key (key of the ciphertexts to pack)
+    /// * `sk_after` - an RLWE secret key (key of the packed ciphertext)
+    /// * `base_log` - the log2 of the decomposition base
+    /// * `level` - the number of levels of the decomposition
+    ///
+    /// # Output
+    /// * an RLWEKSK
+    /// * ValueError - if the decomposition parameters are invalid
+    #[new]
+    pub fn new(
+        sk_before: &crate::LWESecretKey,
+        sk_after: &crate::RLWESecretKey,
+        base_log: usize,
+        level: usize,
+    ) -> PyResult<RLWEKSK> {
+        let bits = <Torus as concrete_commons::numeric::Numeric>::BITS;
+        if base_log == 0 || level == 0 || base_log * level > bits {
+            return Err(PyValueError::new_err(format!(
+                "invalid decomposition: base_log = {} and level = {} (base_log * level must be in [1, {}])",
+                base_log, level, bits)));
+        }
+        let size = sk_after.data.polynomial_size;
+        let dimension_before = sk_before.data.dimension;
+        // the constant coefficient of the (i, l)-th plaintext is s_i * q / B^l
+        let mut plaintexts: Vec<Torus> = vec![0; dimension_before * level * size];
+        for (i, s) in sk_before.data.val.as_tensor().as_slice().iter().enumerate() {
+            for l in 1..=level {
+                plaintexts[(i * level + l - 1) * size] = s << (bits - base_log * l);
+            }
+        }
+        let mut ciphertexts = translate_error!(concrete::VectorRLWE::zero(
+            size, sk_after.data.dimension, dimension_before * level))?;
+        translate_error!(ciphertexts.encrypt_packed_raw(&sk_after.data, &plaintexts))?;
+        Ok(RLWEKSK {
+            ciphertexts, base_log, level, dimension_before, variance: sk_after.data.get_variance(),
+        })
+    }
+
+    #[getter]
+    pub fn get_base_log(&self) -> usize {
+        self.base_log
+    }
+
+    #[getter]
+    pub fn get_level(&self) -> usize {
+        self.level
+    }
+
+    #[getter]
+    pub fn get_dimension_before(&self) -> usize {
+        self.dimension_before
+    }
+
+    #[getter]
+    pub fn get_polynomial_size(&self) -> usize {
+        self.ciphertexts.polynomial_size
+    }
+
+    #[getter]
+    pub fn get_dimension(&self) -> usize {
+        self.ciphertexts.dimension
+    }
+
+    #[getter]
+    pub fn get_variance(&self) -> f64 {
+        self.variance
+    }
+
+    pub fn save(&self, path: &str) -> PyResult<()> {
+        let bytes = translate_error!(bincode::serialize(self))?;
+        translate_error!(std::fs::write(path, bytes))
+    }
+
+    #[staticmethod]
+    pub fn load(path: &str) -> PyResult<RLWEKSK> {
+        let bytes = translate_error!(std::fs::read(path))?;
+        RLWEKSK::_from_bytes(&bytes)
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<RLWEKSK, _>(py, self)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__ (or save)
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<RLWEKSK> {
+        // a corrupted length prefix makes bincode panic on the allocation
+        let key: RLWEKSK = crate::helper_catch_panic(|| crate::helper_from_bytes(bytes))??;
+        key.check_shape()?;
+        Ok(key)
+    }
+
+    pub fn __repr__(&self) -> String {
+        format!(
+            "RLWEKSK(dimension_before={}, polynomial_size={}, dimension={}, base_log={}, level={}, variance={:e})",
+            self.dimension_before, self.ciphertexts.polynomial_size, self.ciphertexts.dimension,
+            self.base_log, self.level, self.variance)
+    }
+}
+
+impl RLWEKSK {
+    /// Pack the ciphertexts of `lwe` into the first slots of a single RLWE ciphertext
+    pub(crate) fn pack(&self, lwe: &concrete::VectorLWE) -> Result<concrete::VectorRLWE, String> {
+        let size = self.ciphertexts.polynomial_size;
+        let dimension = self.ciphertexts.dimension;
+        if lwe.dimension != self.dimension_before {
+            return Err(format!(
+                "DimensionError: the ciphertexts have dimension {} but the key expects {}",
+                lwe.dimension, self.dimension_before));
+        }
+        if lwe.nb_ciphertexts > size {
+            return Err(format!(
+                "{} ciphertexts do not fit in a polynomial of size {}", lwe.nb_ciphertexts, size));
+        }
+        let glwe_len = (dimension + 1) * size;
+        let decomposer = SignedDecomposer::<Torus>::new(
+            DecompositionBaseLog(self.base_log), DecompositionLevelCount(self.level));
+        let keys = self.ciphertexts.ciphertexts.as_tensor().as_slice();
+
+        let mut res = concrete::VectorRLWE::zero(size, dimension, 1).map_err(|e| e.to_string())?;
+        let out = res.ciphertexts.as_mut_tensor().as_mut_slice();
+        for (t, ct) in lwe.ciphertexts.as_tensor().as_slice().chunks(lwe.dimension + 1).enumerate() {
+            // body: b * X^t
+            out[dimension * size + t] = out[dimension * size + t].wrapping_add(ct[lwe.dimension]);
+            // mask: - sum_i sum_l dec_l(a_i) * X^t * KSK(i, l)
+            for (i, a) in ct[..lwe.dimension].iter().enumerate() {
+                for term in decomposer.decompose(decomposer.closest_representable(*a)) {
+                    let d = term.value();
+                    if d == 0 {
+                        continue;
+                    }
+                    let k = i * self.level + term.level().0 - 1;
+                    let key = &keys[k * glwe_len..(k + 1) * glwe_len];
+                    for (out_poly, key_poly) in out.chunks_mut(size).zip(key.chunks(size)) {
+                        for (c, coeff) in key_poly.iter().enumerate() {
+                            let prod = d.wrapping_mul(*coeff);
+                            if c + t < size {
+                                out_poly[c + t] = out_poly[c + t].wrapping_sub(prod);
+                            } else {
+                                out_poly[c + t - size] = out_poly[c + t - size].wrapping_add(prod);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+        for (t, (encoder, variance)) in lwe.encoders.iter().zip(lwe.variances.iter()).enumerate() {
+            res.encoders[t] = encoder.clone();
+            res.variances[t] = <Torus as npe::LWE>::key_switch(
+                self.dimension_before, self.level, self.base_log, self.variance, *variance);
+        }
+        Ok(res)
+    }
+}
+
+pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<RLWEKSK>()?;
+
+    Ok(())
+}
@@ -8,7 +8,7 @@ use super::translate_error;
 /// - `polynomial_size`: the number of coefficients in a polynomial
 /// - `dimension`: the size of an RLWE mask
 /// - `log2_std_dev`: the log2 of the standard deviation used for the error normal distribution
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct RLWEParams {
     // pub polynomial_size: usize,
@@ -203,6 +203,18 @@ impl RLWEParams {
         Ok(RLWEParams{ data })
     }
 
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<RLWEParams, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<RLWEParams> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(RLWEParams{ data })
+    }
+
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
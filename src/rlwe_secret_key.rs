@@ -3,7 +3,7 @@ use pyo3::exceptions::*;
 use concrete;
 use super::{translate_error, LWESecretKey};
 
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, PartialEq)]
 pub struct RLWESecretKey {
     // pub val: GlweSecretKey<BinaryKeyKind, Vec<u64>> ,
@@ -76,6 +76,13 @@ impl RLWESecretKey {
         LWESecretKey{ data }
     }
 
+    /// Return the dimension of the LWE secret key given by to_lwe_secret_key
+    /// # Output
+    /// * dimension * polynomial_size
+    pub fn get_lwe_dimension(&self) -> usize {
+        self.data.dimension * self.data.polynomial_size
+    }
+
     /// Return the variance of the error distribution associated with the secret key
     /// # Output
     /// * the variance
@@ -89,7 +96,19 @@ impl RLWESecretKey {
 
     #[staticmethod]
     pub fn load(path: &str) -> PyResult<RLWESecretKey> {
-        let data = concrete::RLWESecretKey::load(path).unwrap();
+        let data = translate_error!(concrete::RLWESecretKey::load(path))?;
+        Ok(RLWESecretKey{ data })
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<RLWESecretKey, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<RLWESecretKey> {
+        let data = crate::helper_from_bytes(bytes)?;
         Ok(RLWESecretKey{ data })
     }
 
@@ -2,10 +2,20 @@
 
 use pyo3::prelude::*;
 use pyo3::exceptions::*;
-use pyo3::types::{PyList, PyFunction};
+use pyo3::types::{PyAny, PyList, PyFunction, PySlice};
 use concrete;
 use concrete::{Torus};
-use super::translate_error;
+use concrete_core::crypto::encoding::Plaintext;
+use concrete_commons::parameters::LweSize;
+use concrete_core::crypto::lwe::{LweCiphertext, LweList};
+use concrete_core::math::tensor::{AsMutSlice, AsMutTensor, AsRefSlice, AsRefTensor};
+use concrete_npe as npe;
+use rayon::prelude::*;
+use flate2::Compression;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use std::io::Write;
+use super::{translate_error, helper_deltas_eq};
 
 /// Structure containing a list of LWE ciphertexts.
 /// They all have the same dimension (i.e. the length of the LWE mask).
@@ -16,7 +26,7 @@ use super::translate_error;
 /// * `dimension` - the length the LWE mask
 /// * `nb_ciphertexts` - the number of LWE ciphertexts present in the list
 /// * `encoders` - the encoders of each LWE ciphertext of the list
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct VectorLWE {
     // pub ciphertexts: LweList<Vec<Torus>>,
@@ -25,11 +35,25 @@ pub struct VectorLWE {
     // pub nb_ciphertexts: usize,
     // pub encoders: Vec<crate::Encoder>,
     pub data: concrete::VectorLWE,
+    /// fingerprint of the secret key used at encryption, None when unknown (e.g. after a bootstrap)
+    pub key_fingerprint: Option<u64>,
+    /// (rows, cols) when the ciphertexts are a 2D grid stored row by row, None for a flat vector
+    /// Kept by the element-wise operations, it is not serialized
+    pub shape: Option<(usize, usize)>,
+}
+
+/// How a batch of bootstraps is scheduled: on at most `max_threads` threads (all the cores by
+/// default), `chunk_size` ciphertexts at a time (all of them by default)
+/// Each thread works on its own copy of the bootstrapping key, dropped when the batch returns;
+/// `chunk_size` bounds the number of outputs alive at once
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct Batch {
+    pub max_threads: Option<usize>,
+    pub chunk_size: Option<usize>,
 }
 
 #[pymethods]
 impl VectorLWE {
-
     #[getter]
     pub fn get_variances(&self) -> Vec<f64> {
         self.data.variances.clone()
@@ -45,9 +69,15 @@ impl VectorLWE {
         self.data.dimension
     }
 
+    /// The dimension is tied to the length of the masks, so it cannot be changed in place
     #[setter]
-    pub fn set_dimension(&mut self, v: usize) {
-        self.data.dimension = v;
+    pub fn set_dimension(&mut self, v: usize) -> PyResult<()> {
+        if v != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "cannot change the dimension of existing ciphertexts from {} to {}",
+                self.data.dimension, v)));
+        }
+        Ok(())
     }
 
     #[getter]
@@ -55,9 +85,26 @@ impl VectorLWE {
         self.data.nb_ciphertexts
     }
 
+    /// Resize the structure: new ciphertexts are zeros (with empty encoders) and extra ones are dropped
     #[setter]
-    pub fn set_nb_ciphertexts(&mut self, v: usize) {
-        self.data.nb_ciphertexts = v;
+    pub fn set_nb_ciphertexts(&mut self, v: usize) -> PyResult<()> {
+        if v == self.data.nb_ciphertexts {
+            return Ok(());
+        }
+        let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, v))?;
+        for i in 0..v.min(self.data.nb_ciphertexts) {
+            translate_error!(data.copy_in_nth_nth_inplace(i, &self.data, i))?;
+        }
+        self.data = data;
+        self.shape = None;
+        Ok(())
+    }
+
+    /// The fingerprint of the secret key used at encryption (see LWESecretKey.fingerprint),
+    /// None when it is unknown, e.g. after a key switch or a bootstrap
+    #[getter]
+    pub fn get_key_fingerprint(&self) -> Option<String> {
+        self.key_fingerprint.map(|f| format!("{:016x}", f))
     }
 
     #[getter]
@@ -94,7 +141,19 @@ impl VectorLWE {
         nb_ciphertexts: usize,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(concrete::VectorLWE::zero(dimension, nb_ciphertexts))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Instantiate a new VectorLWE filled with zeros with the same shape as self,
+    /// the encoders are copied from self and the variances are set to zero
+    ///
+    /// # Output
+    /// * a new VectorLWE with the same dimension, nb_ciphertexts, encoders and 2D shape as self
+    pub fn zeros_like(&self) -> PyResult<crate::VectorLWE> {
+        let mut data = translate_error!(concrete::VectorLWE::zero(
+            self.data.dimension, self.data.nb_ciphertexts))?;
+        data.encoders = self.data.encoders.clone();
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: self.shape })
     }
 
     /// Copy one ciphertext from an VectorLWE structure inside the self VectorLWE structure
@@ -150,7 +209,55 @@ impl VectorLWE {
     /// ```
     pub fn extract_nth(&self, n: usize) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.extract_nth(n))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Return the 2D shape of the structure
+    ///
+    /// # Output
+    /// * (rows, cols) if the ciphertexts were given a grid shape with reshape, None for a flat vector
+    pub fn shape(&self) -> Option<(usize, usize)> {
+        self.shape
+    }
+
+    /// View the ciphertexts as a 2D grid stored row by row, the ciphertexts are not copied around
+    ///
+    /// # Arguments
+    /// * `rows` - the number of rows
+    /// * `cols` - the number of columns
+    ///
+    /// # Output
+    /// * a copy of self with the shape (rows, cols)
+    /// * ValueError if rows * cols != self.nb_ciphertexts
+    pub fn reshape(&self, rows: usize, cols: usize) -> PyResult<crate::VectorLWE> {
+        if rows.checked_mul(cols) != Some(self.data.nb_ciphertexts) {
+            return Err(PyValueError::new_err(format!(
+                "cannot reshape {} ciphertexts into {}x{}", self.data.nb_ciphertexts, rows, cols)));
+        }
+        Ok(VectorLWE{ data: self.data.clone(), key_fingerprint: self.key_fingerprint, shape: Some((rows, cols)) })
+    }
+
+    /// Extract the LWE ciphertext at row `i` and column `j` of a 2D structure (see reshape)
+    ///
+    /// # Arguments
+    /// * `i` - the row
+    /// * `j` - the column
+    ///
+    /// # Output
+    /// * an LWE
+    /// * ValueError if self has no 2D shape
+    /// * IndexError if (i, j) is outside of the grid
+    pub fn get_2d(&self, i: usize, j: usize) -> PyResult<crate::LWE> {
+        let (rows, cols) = match self.shape {
+            Some(shape) => shape,
+            None => return Err(PyValueError::new_err("the VectorLWE has no 2D shape, call reshape first")),
+        };
+        if i >= rows || j >= cols {
+            return Err(PyIndexError::new_err(format!(
+                "position ({}, {}) out of range for a {}x{} grid", i, j, rows, cols)));
+        }
+        let data = VectorLWE::nth_as_lwe(&self.data, i * cols + j)?;
+        Ok(crate::LWE{ data })
     }
 
     /// Encrypt plaintexts from a Plaintext with the provided LWEParams
@@ -187,7 +294,31 @@ impl VectorLWE {
         plaintexts: &crate::Plaintext,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(concrete::VectorLWE::encrypt(&sk.data, &plaintexts.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: Some(crate::LWESecretKey::fingerprint_of(&sk.data)), shape: None })
+    }
+
+    /// Encrypt several raw plaintexts like encrypt, after checking the dimension of the key
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `plaintexts` - a Plaintext
+    /// * `expected_dimension` - the dimension the key must have
+    ///
+    /// # Output
+    /// * VectorLWE structure
+    /// * DimensionError - if the dimension of sk is not expected_dimension
+    #[staticmethod]
+    pub fn encrypt_checked(
+        sk: &crate::LWESecretKey,
+        plaintexts: &crate::Plaintext,
+        expected_dimension: usize,
+    ) -> PyResult<crate::VectorLWE> {
+        if sk.data.dimension != expected_dimension {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the secret key has dimension {} but {} is expected",
+                sk.data.dimension, expected_dimension)));
+        }
+        VectorLWE::encrypt(sk, plaintexts)
     }
 
     /// Encode messages and then directly encrypt the plaintexts into an VectorLWE structure
@@ -222,9 +353,174 @@ impl VectorLWE {
         messages: Vec<f64>,
         encoder: &crate::Encoder,
     ) -> PyResult<VectorLWE> {
+        if let Some((i, m)) = messages.iter().enumerate().find(|(_, m)| !encoder.contains(**m)) {
+            return Err(PyValueError::new_err(format!(
+                "message {} at index {} is outside of the interval [{}, {}[",
+                m, i, encoder.get_min(), encoder.get_min() + encoder.get_delta())));
+        }
         let data = translate_error!(concrete::VectorLWE::encode_encrypt(
             &sk.data, &messages, &encoder.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: Some(crate::LWESecretKey::fingerprint_of(&sk.data)), shape: None })
+    }
+
+    /// Encode and encrypt the messages of a Python iterable (e.g. a generator) one at a time,
+    /// appending each ciphertext to the buffer of the structure, so that the messages are never
+    /// gathered in a list
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `messages` - an iterable of floats
+    /// * `encoder` - an Encoder
+    ///
+    /// # Output
+    /// * a VectorLWE with one ciphertext per message, in the order of the iteration
+    /// * ValueError - if a message is outside of the interval or there is no message
+    /// * TypeError - if `messages` is not iterable or yields something else than floats
+    #[staticmethod]
+    pub fn encode_encrypt_iter(
+        sk: &crate::LWESecretKey,
+        messages: &PyAny,
+        encoder: &crate::Encoder,
+    ) -> PyResult<VectorLWE> {
+        let dimension = sk.data.dimension;
+        let mut ciphertexts: Vec<Torus> = Vec::new();
+        let mut variances = Vec::new();
+        for (i, m) in messages.iter()?.enumerate() {
+            let m: f64 = m?.extract()?;
+            if !encoder.contains(m) {
+                return Err(PyValueError::new_err(format!(
+                    "message {} at index {} is outside of the interval [{}, {}[",
+                    m, i, encoder.get_min(), encoder.get_min() + encoder.get_delta())));
+            }
+            let ct = translate_error!(concrete::LWE::encode_encrypt(&sk.data, m, &encoder.data))?;
+            ciphertexts.extend_from_slice(ct.ciphertext.as_tensor().as_slice());
+            variances.push(ct.variance);
+        }
+        let nb_ciphertexts = variances.len();
+        if nb_ciphertexts == 0 {
+            return Err(PyValueError::new_err("no message to encrypt"));
+        }
+        let data = concrete::VectorLWE {
+            ciphertexts: LweList::from_container(ciphertexts, LweSize(dimension + 1)),
+            variances,
+            dimension,
+            nb_ciphertexts,
+            encoders: vec![encoder.data.clone(); nb_ciphertexts],
+        };
+        Ok(VectorLWE{ data, key_fingerprint: Some(crate::LWESecretKey::fingerprint_of(&sk.data)), shape: None })
+    }
+
+    /// Encode and encrypt several messages with the noise level 2^log2_std_dev instead of the one of the key
+    /// This is meant for research on noise growth: a noise smaller than the one of the key
+    /// weakens the security of the ciphertexts
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `messages` - a list of messages as f64
+    /// * `encoder` - an Encoder
+    /// * `log2_std_dev` - the log2 of the standard deviation of the noise, at least -62
+    ///
+    /// # Output
+    /// * a new VectorLWE
+    /// * NoNoiseInCiphertext - if the standard deviation is too small to produce any noise
+    #[staticmethod]
+    pub fn encode_encrypt_with_std(
+        sk: &crate::LWESecretKey,
+        messages: Vec<f64>,
+        encoder: &crate::Encoder,
+        log2_std_dev: i32,
+    ) -> PyResult<VectorLWE> {
+        let min_log2_std_dev = -(<Torus>::BITS as i32) + 2;
+        if log2_std_dev < min_log2_std_dev {
+            return Err(PyValueError::new_err(format!(
+                "NoNoiseInCiphertext: a standard deviation of 2^{} is too small to produce noise (at least 2^{})",
+                log2_std_dev, min_log2_std_dev)));
+        }
+        let mut key = sk.clone();
+        key.data.std_dev = f64::powi(2., log2_std_dev);
+        VectorLWE::encode_encrypt(&key, messages, encoder)
+    }
+
+    /// Encode and encrypt several messages, and also return the values the ciphertexts
+    /// decrypt to without noise, i.e. the messages decoded right after being encoded
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `messages` - a list of messages as f64
+    /// * `encoder` - an Encoder
+    ///
+    /// # Output
+    /// * a tuple (new VectorLWE, reference values)
+    #[staticmethod]
+    pub fn encode_encrypt_debug(
+        sk: &crate::LWESecretKey,
+        messages: Vec<f64>,
+        encoder: &crate::Encoder,
+    ) -> PyResult<(VectorLWE, Vec<f64>)> {
+        let ct = VectorLWE::encode_encrypt(sk, messages.clone(), encoder)?;
+        let plaintext = translate_error!(encoder.data.encode(&messages))?;
+        let reference = translate_error!(plaintext.decode())?;
+        Ok((ct, reference))
+    }
+
+    /// Encrypt the binary decomposition of an integer, one boolean ciphertext per bit
+    /// (least significant bit first), with the canonical boolean encoder of LWE.encrypt_bool
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `value` - the integer to decompose
+    /// * `nb_bits` - the number of bits to encrypt, between 1 and 64
+    /// * `padding` - number of bits of padding of each ciphertext
+    ///
+    /// # Output
+    /// * a new VectorLWE with nb_bits ciphertexts encrypting 0. or 1.
+    /// * ValueError - if nb_bits is not in [1, 64] or value does not fit in nb_bits bits
+    #[staticmethod]
+    pub fn encode_encrypt_bits(
+        sk: &crate::LWESecretKey,
+        value: u64,
+        nb_bits: usize,
+        padding: usize,
+    ) -> PyResult<VectorLWE> {
+        if nb_bits == 0 || nb_bits > 64 {
+            return Err(PyValueError::new_err(format!("nb_bits must be in [1, 64], got {}", nb_bits)));
+        }
+        if nb_bits < 64 && value >> nb_bits != 0 {
+            return Err(PyValueError::new_err(format!("{} does not fit in {} bits", value, nb_bits)));
+        }
+        let encoder = translate_error!(concrete::Encoder::new(0., 1., 2, padding))?;
+        let messages: Vec<f64> = (0..nb_bits).map(|i| ((value >> i) & 1) as f64).collect();
+        let data = translate_error!(concrete::VectorLWE::encode_encrypt(&sk.data, &messages, &encoder))?;
+        Ok(VectorLWE{ data, key_fingerprint: Some(crate::LWESecretKey::fingerprint_of(&sk.data)), shape: None })
+    }
+
+    /// Encode and encrypt a matrix (a list of rows or a 2D NumPy array) in row-major order
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `matrix` - the messages, all the rows having the same length
+    /// * `encoder` - an Encoder
+    ///
+    /// # Output
+    /// * a new VectorLWE with the flattened matrix and the shape (rows, columns) of the matrix
+    #[staticmethod]
+    pub fn encode_encrypt_matrix(
+        sk: &crate::LWESecretKey,
+        matrix: Vec<Vec<f64>>,
+        encoder: &crate::Encoder,
+    ) -> PyResult<(VectorLWE, (usize, usize))> {
+        let rows = matrix.len();
+        let cols = matrix.first().map_or(0, |row| row.len());
+        if rows == 0 || cols == 0 {
+            return Err(PyValueError::new_err("the matrix is empty"));
+        }
+        if let Some(i) = matrix.iter().position(|row| row.len() != cols) {
+            return Err(PyValueError::new_err(format!(
+                "row {} has {} values, expected {}", i, matrix[i].len(), cols)));
+        }
+        let messages: Vec<f64> = matrix.into_iter().flatten().collect();
+        let res = VectorLWE::encode_encrypt(sk, messages, encoder)?;
+        Ok((res, (rows, cols)))
     }
 
     /// Encode messages with a different encoder for each message and encrypt them
@@ -272,7 +568,7 @@ impl VectorLWE {
             |x| x.extract::<crate::Encoder>().unwrap().data).collect();
         let data = translate_error!(concrete::VectorLWE::encode_encrypt_several_encoders(
             &sk.data, &messages, &tmp))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: Some(crate::LWESecretKey::fingerprint_of(&sk.data)), shape: None })
     }
 
     /// Encrypt plaintexts from a Plaintext with the provided LWEParams
@@ -302,12 +598,22 @@ impl VectorLWE {
     /// let mut ct = VectorLWE::zero(sk.dimension, messages.len()).unwrap();
     /// ct.encrypt_inplace(&sk, &pt).unwrap();
     /// ```
+    /// If the number of ciphertexts differs from the number of plaintexts, self is resized first
+    /// and becomes a flat vector (its 2D shape, if any, is dropped)
     pub fn encrypt_inplace(
         &mut self,
         sk: &crate::LWESecretKey,
         plaintexts: &crate::Plaintext,
     ) -> PyResult<()> {
-        translate_error!(self.data.encrypt_inplace(&sk.data, &plaintexts.data))
+        self.check_key_dimension(sk)?;
+        if self.data.nb_ciphertexts != plaintexts.data.nb_plaintexts {
+            self.data = translate_error!(concrete::VectorLWE::zero(
+                self.data.dimension, plaintexts.data.nb_plaintexts))?;
+            self.shape = None;
+        }
+        translate_error!(self.data.encrypt_inplace(&sk.data, &plaintexts.data))?;
+        self.key_fingerprint = Some(crate::LWESecretKey::fingerprint_of(&sk.data));
+        Ok(())
     }
 
     /// Encrypt several raw plaintexts (list of Torus element instead of a struct Plaintext) with the provided key and standard deviation
@@ -338,16 +644,22 @@ impl VectorLWE {
         sk: &crate::LWESecretKey,
         plaintexts: Vec<Torus>,
     ) -> PyResult<()> {
-        translate_error!(self.data.encrypt_raw(&sk.data, &plaintexts))
+        self.check_key_dimension(sk)?;
+        translate_error!(self.data.encrypt_raw(&sk.data, &plaintexts))?;
+        self.key_fingerprint = Some(crate::LWESecretKey::fingerprint_of(&sk.data));
+        Ok(())
     }
 
     /// Decrypt the list of ciphertexts, meaning compute the phase and directly decode the output
+    /// When the fingerprint of the encryption key is known, it is compared with the one of `sk`
     ///
     /// # Arguments
     /// * `sk` - an LWE secret key
+    /// * `key_check` - what to do when the fingerprints differ: "warn" (default), "raise" or "ignore"
     /// # Output
     /// * `result` - a list of messages as f64
     /// * DimensionError - if the ciphertext and the key have incompatible dimensions
+    /// * ValueError - if the fingerprints differ and key_check is "raise"
     /// ```rust
     /// use concrete::*;
     ///
@@ -369,8 +681,82 @@ impl VectorLWE {
     ///
     /// let res = ct.decrypt_decode(&sk).unwrap();
     /// ```
-    pub fn decrypt_decode(&self, sk: &crate::LWESecretKey) -> PyResult<Vec<f64>> {
-        translate_error!(self.data.decrypt_decode(&sk.data))
+    #[args(key_check = "\"warn\"")]
+    pub fn decrypt_decode(&self, py: Python, sk: &crate::LWESecretKey, key_check: &str) -> PyResult<Vec<f64>> {
+        self.check_key_fingerprint(py, sk, key_check)?;
+        self.decrypt_decode_range(sk, 0..self.data.nb_ciphertexts)
+    }
+
+    /// Decrypt and decode only the n-th ciphertext of the list
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `n` - the index of the ciphertext to decrypt
+    /// # Output
+    /// * the decoded message as f64
+    /// * IndexError - if n is out of range
+    pub fn decrypt_decode_nth(&self, sk: &crate::LWESecretKey, n: usize) -> PyResult<f64> {
+        if n >= self.data.nb_ciphertexts {
+            return Err(PyIndexError::new_err(format!(
+                "index {} out of range for {} ciphertexts", n, self.data.nb_ciphertexts)));
+        }
+        Ok(self.decrypt_decode_range(sk, n..n + 1)?[0])
+    }
+
+    /// Decrypt and decode the list of ciphertexts and check if one of the messages is close
+    /// to `value`. This is a client-side helper (it needs the secret key), not a homomorphic test
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `value` - the message to look for
+    /// * `tol` - the largest accepted distance between a message and `value`
+    /// # Output
+    /// * true if some decrypted message is within `tol` of `value`
+    pub fn decrypt_contains(&self, sk: &crate::LWESecretKey, value: f64, tol: f64) -> PyResult<bool> {
+        let messages = self.decrypt_decode_range(sk, 0..self.data.nb_ciphertexts)?;
+        Ok(messages.iter().any(|m| (m - value).abs() <= tol))
+    }
+
+    /// Decrypt ciphertexts produced by encode_encrypt_bits and reassemble the integer,
+    /// each message being rounded to the closest bit
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// # Output
+    /// * the integer whose i-th bit is the i-th ciphertext
+    /// * ValueError - if there are more than 64 ciphertexts
+    pub fn decrypt_bits(&self, sk: &crate::LWESecretKey) -> PyResult<u64> {
+        if self.data.nb_ciphertexts > 64 {
+            return Err(PyValueError::new_err(format!(
+                "{} bits do not fit in a 64-bit integer", self.data.nb_ciphertexts)));
+        }
+        let messages = self.decrypt_decode_range(sk, 0..self.data.nb_ciphertexts)?;
+        Ok(messages.iter().enumerate().fold(0, |acc, (i, m)| acc | (((*m >= 0.5) as u64) << i)))
+    }
+
+    /// Decrypt and decode the list of ciphertexts into a NumPy array of floats where the messages
+    /// of the ciphertexts with less than `min_precision_bits` bits of precision left (counting
+    /// the noise, see min_noise_budget_bits) are NaN
+    ///
+    /// # Arguments
+    /// * `sk` - an LWE secret key
+    /// * `min_precision_bits` - the precision under which a message is not reliable
+    /// # Output
+    /// * a new NumPy array of length nb_ciphertexts
+    /// * ImportError if NumPy is not installed
+    pub fn decrypt_decode_masked(
+        &self,
+        py: Python,
+        sk: &crate::LWESecretKey,
+        min_precision_bits: usize,
+    ) -> PyResult<PyObject> {
+        let messages: Vec<f64> = self.decrypt_decode_range(sk, 0..self.data.nb_ciphertexts)?
+            .into_iter().zip(self.noise_budgets())
+            .map(|(m, bits)| if bits < min_precision_bits { f64::NAN } else { m })
+            .collect();
+        let numpy = py.import("numpy")?;
+        let array = numpy.call_method1("array", (messages, numpy.getattr("float64")?))?;
+        Ok(array.into())
     }
 
     /// Decrypt the list of ciphertexts, meaning compute the phase and directly decode the output
@@ -440,6 +826,22 @@ impl VectorLWE {
         translate_error!(self.data.decrypt_decode_round(&sk.data))
     }
 
+    /// Decrypt the list of ciphertexts and decode each of them with its own encoder, rounding
+    /// only the ones whose encoder has its `round` flag set
+    ///
+    /// # Argument
+    /// * `sk` - an LWE secret key
+    ///
+    /// # Output
+    /// * a list of messages as f64
+    /// * DimensionError - if the ciphertext and the key have incompatible dimensions
+    pub fn decrypt(&self, sk: &crate::LWESecretKey) -> PyResult<Vec<f64>> {
+        let raw = translate_error!(self.data.decrypt_raw(&sk.data))?;
+        raw.iter().zip(self.data.encoders.iter())
+            .map(|(pt, enc)| translate_error!(enc.decode_single(*pt)))
+            .collect()
+    }
+
     /// Add small messages to a VectorLWE ciphertext and does not change the encoding but changes the bodies of the ciphertexts
     ///
     /// # Argument
@@ -474,7 +876,7 @@ impl VectorLWE {
         messages: Vec<f64>,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.add_constant_static_encoder(&messages))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Add small messages to a VectorLWE ciphertext and does not change the encoding but changes the bodies of the ciphertexts
@@ -549,7 +951,7 @@ impl VectorLWE {
         messages: Vec<f64>,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.add_constant_dynamic_encoder(&messages))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Add messages to a VectorLWE ciphertext and translate the interval of a distance equal to the message but does not change either the bodies or the masks of the ciphertexts
@@ -590,6 +992,21 @@ impl VectorLWE {
         translate_error!(self.data.add_constant_dynamic_encoder_inplace(&messages))
     }
 
+    /// Add the same message to every ciphertext by translating their intervals,
+    /// like add_constant_dynamic_encoder with the message repeated for each ciphertext
+    ///
+    /// # Argument
+    /// * `message` - the message to add, as f64
+    ///
+    /// # Output
+    /// * a new VectorLWE
+    /// * InvalidEncoderError if invalid encoder
+    pub fn add_scalar(&self, message: f64) -> PyResult<crate::VectorLWE> {
+        let messages = vec![message; self.data.nb_ciphertexts];
+        let data = translate_error!(self.data.add_constant_dynamic_encoder(&messages))?;
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
+    }
+
     /// Compute an homomorphic addition between two VectorLWE ciphertexts
     ///
     /// # Arguments
@@ -636,7 +1053,7 @@ impl VectorLWE {
         new_min: Vec<f64>,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.add_with_new_min(&ct.data, &new_min))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Compute an homomorphic addition between two VectorLWE ciphertexts
@@ -686,6 +1103,72 @@ impl VectorLWE {
         translate_error!(self.data.add_with_new_min_inplace(&ct.data, &new_min))
     }
 
+    /// Add element-wise the ciphertexts of self and other: the i-th output is
+    /// self[i] + other[i] with an interval starting at new_mins[i]
+    ///
+    /// # Arguments
+    /// * `other` - a VectorLWE with as many ciphertexts as self
+    /// * `new_mins` - the minimum of the output interval of each sum
+    ///
+    /// # Output
+    /// * a new VectorLWE
+    pub fn zip_add(
+        &self,
+        other: &crate::VectorLWE,
+        new_mins: Vec<f64>,
+    ) -> PyResult<crate::VectorLWE> {
+        if other.data.nb_ciphertexts != self.data.nb_ciphertexts {
+            return Err(PyValueError::new_err(format!(
+                "expected {} ciphertexts in other, got {}",
+                self.data.nb_ciphertexts, other.data.nb_ciphertexts)));
+        }
+        if new_mins.len() != self.data.nb_ciphertexts {
+            return Err(PyValueError::new_err(format!(
+                "expected {} values in new_mins, got {}",
+                self.data.nb_ciphertexts, new_mins.len())));
+        }
+        self.add_with_new_min(other, new_mins)
+    }
+
+    /// Return copies of self and other whose i-th ciphertexts share the same delta and padding,
+    /// so that they can be added with add_with_padding
+    /// A delta 2^k times smaller is widened by reinterpreting k bits of padding as message bits
+    /// (only the encoder changes), then the extra padding of one side is removed
+    ///
+    /// # Argument
+    /// * `other` - a VectorLWE with as many ciphertexts as self
+    ///
+    /// # Output
+    /// * the aligned copies of self and other
+    /// * ValueError - if the deltas are not a power of 2 apart or there is not enough padding
+    pub fn align_encoders_with(
+        &self,
+        other: &crate::VectorLWE,
+    ) -> PyResult<(crate::VectorLWE, crate::VectorLWE)> {
+        let nb = self.data.nb_ciphertexts;
+        if other.data.nb_ciphertexts != nb {
+            return Err(PyValueError::new_err(format!(
+                "expected {} ciphertexts in other, got {}", nb, other.data.nb_ciphertexts)));
+        }
+        let mut xs = Vec::with_capacity(nb);
+        let mut ys = Vec::with_capacity(nb);
+        for i in 0..nb {
+            let mut x = VectorLWE::nth_as_lwe(&self.data, i)?;
+            let mut y = VectorLWE::nth_as_lwe(&other.data, i)?;
+            if x.encoder.delta <= y.encoder.delta {
+                VectorLWE::align_pair(&mut x, &mut y)
+            } else {
+                VectorLWE::align_pair(&mut y, &mut x)
+            }.map_err(|e| PyValueError::new_err(format!("ciphertext {}: {}", i, e)))?;
+            xs.push(x);
+            ys.push(y);
+        }
+        Ok((
+            VectorLWE{ data: VectorLWE::from_lwes(&xs)?, key_fingerprint: self.key_fingerprint, shape: None },
+            VectorLWE{ data: VectorLWE::from_lwes(&ys)?, key_fingerprint: other.key_fingerprint, shape: None },
+        ))
+    }
+
     /// Compute an homomorphic addition between two VectorLWE ciphertexts.
     /// The center of the output Encoder is the sum of the two centers of the input Encoders.
     /// # Arguments
@@ -726,7 +1209,7 @@ impl VectorLWE {
     /// ```
     pub fn add_centered(&self, ct: &crate::VectorLWE) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.add_centered(&ct.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Compute an homomorphic addition between two VectorLWE ciphertexts.
@@ -813,7 +1296,7 @@ impl VectorLWE {
         ct: &crate::VectorLWE,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.add_with_padding(&ct.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Compute an addition between two VectorLWE ciphertexts by eating one bit of padding
@@ -855,6 +1338,89 @@ impl VectorLWE {
         translate_error!(self.data.add_with_padding_inplace(&ct.data))
     }
 
+    /// Compute an addition between two VectorLWE ciphertexts as add_with_padding, first
+    /// refreshing the operands whose padding is running out so that long chains of additions
+    /// do not need manual bootstraps
+    /// At each position where self or ct has less than 2 bits of padding (the sum would have none
+    /// left and could not be bootstrapped anymore), both operands are brought to
+    /// `refresh_padding` bits of padding with a key switch followed by a bootstrap of the identity
+    /// (in parallel); an operand already at `refresh_padding` bits is left as is
+    /// Each refresh costs a key switch and a bootstrap per ciphertext, i.e. as much as
+    /// refresh_all on the refreshed positions, and resets their noise to the bootstrap noise
+    ///
+    /// # Argument
+    /// * `ct` - an VectorLWE struct, under the same key as self
+    /// * `ksk` - a key switching key from the key of the operands to the input key of `bsk`
+    /// * `bsk` - a bootstrapping key whose output key is the key of the operands
+    /// * `refresh_padding` - the padding of the refreshed operands, at least 2
+    ///
+    /// # Output
+    /// * a new VectorLWE, without known key fingerprint if some operand was refreshed
+    /// * ValueError - if the lengths differ or `refresh_padding` is less than 2
+    /// * DimensionError - if the keys do not form a cycle around the key of the operands
+    /// * DeltaError - if the ciphertexts have incompatible deltas
+    /// * PaddingError - if the ciphertexts have incompatible paddings
+    /// * NotEnoughPaddingError - if an operand to refresh has no padding left
+    #[args(refresh_padding = "2")]
+    pub fn add_refreshing(
+        &self,
+        py: Python,
+        ct: &crate::VectorLWE,
+        ksk: &crate::LWEKSK,
+        bsk: &crate::LWEBSK,
+        refresh_padding: usize,
+    ) -> PyResult<crate::VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        if ct.data.nb_ciphertexts != nb {
+            return Err(PyValueError::new_err(format!(
+                "length mismatch: {} and {} ciphertexts", nb, ct.data.nb_ciphertexts)));
+        }
+        if refresh_padding < 2 {
+            return Err(PyValueError::new_err(format!(
+                "a refresh must restore at least 2 bits of padding, got {}", refresh_padding)));
+        }
+        let dimension = bsk.data.dimension * bsk.data.polynomial_size;
+        if ksk.data.dimension_before != dimension || ksk.data.dimension_after != bsk.data.get_lwe_dimension() {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the key switching key goes from dimension {} to {} but the bootstrapping key goes from {} to {}",
+                ksk.data.dimension_before, ksk.data.dimension_after, bsk.data.get_lwe_dimension(), dimension)));
+        }
+        let low: Vec<usize> = (0..nb).filter(|&i| {
+            self.data.encoders[i].nb_bit_padding < 2 || ct.data.encoders[i].nb_bit_padding < 2
+        }).collect();
+        let (mut a, mut b) = (self.data.clone(), ct.data.clone());
+        let mut refreshed = false;
+        for operand in [&mut a, &mut b] {
+            let todo: Vec<usize> = low.iter().cloned()
+                .filter(|&i| operand.encoders[i].nb_bit_padding != refresh_padding)
+                .collect();
+            if todo.is_empty() {
+                continue;
+            }
+            if operand.dimension != dimension {
+                return Err(PyValueError::new_err(format!(
+                    "DimensionError: the ciphertexts have dimension {} but the bootstrapping key outputs {}",
+                    operand.dimension, dimension)));
+            }
+            let source = &*operand;
+            let todo_ref = &todo;
+            let res = VectorLWE::parallel_bootstrap(py, bsk, todo.len(), Batch::default(), |key, j| {
+                let input = VectorLWE::nth_as_lwe(source, todo_ref[j]).map_err(|e| e.to_string())?;
+                let mut encoder = input.encoder.clone();
+                encoder.nb_bit_padding = refresh_padding;
+                let switched = input.keyswitch(&ksk.data).map_err(|e| e.to_string())?;
+                switched.bootstrap_with_function(key, |x| x, &encoder).map_err(|e| e.to_string())
+            })?;
+            for (j, i) in todo.iter().enumerate() {
+                translate_error!(operand.copy_in_nth_nth_inplace(*i, &res, j))?;
+            }
+            refreshed = true;
+        }
+        let data = translate_error!(a.add_with_padding(&b))?;
+        let key_fingerprint = if refreshed { None } else { self.key_fingerprint };
+        Ok(VectorLWE{ data, key_fingerprint, shape: self.shape })
+    }
+
     /// Compute an subtraction between two VectorLWE ciphertexts by eating one bit of padding
     ///
     /// # Argument
@@ -893,7 +1459,7 @@ impl VectorLWE {
         ct: &crate::VectorLWE,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.sub_with_padding(&ct.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Compute an subtraction between two VectorLWE ciphertexts by eating one bit of padding
@@ -971,8 +1537,9 @@ impl VectorLWE {
         &self,
         messages: Vec<i32>,
     ) -> PyResult<crate::VectorLWE> {
+        self.check_static_product_range(&messages)?;
         let data = translate_error!(self.data.mul_constant_static_encoder(&messages))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Multiply VectorLWE ciphertexts with small integer messages and does not change the encoding but changes the bodies and masks of the ciphertexts
@@ -1010,6 +1577,7 @@ impl VectorLWE {
         &mut self,
         messages: Vec<i32>,
     ) -> PyResult<()> {
+        self.check_static_product_range(&messages)?;
         translate_error!(self.data.mul_constant_static_encoder_inplace(&messages))
     }
 
@@ -1060,7 +1628,7 @@ impl VectorLWE {
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.mul_constant_with_padding(
             &constants, max_constant, nb_bit_padding))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: self.shape })
     }
 
     /// Multiply each LWE ciphertext with a real constant and do change the encoding and the ciphertexts by consuming some bits of padding
@@ -1147,7 +1715,7 @@ impl VectorLWE {
     /// ```
     pub fn opposite_nth(&self, n: usize) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.opposite_nth(n))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
     }
 
     /// Compute the opposite of the n-th LWE ciphertext in the structure
@@ -1185,6 +1753,90 @@ impl VectorLWE {
         translate_error!(self.data.opposite_nth_inplace(n))
     }
 
+    /// Compute the opposite of all the LWE ciphertexts in the structure
+    ///
+    /// # Output
+    /// * a new VectorLWE ciphertext
+    /// * InvalidEncoderError - if one of the encoders is invalid
+    pub fn opposite(&self) -> PyResult<crate::VectorLWE> {
+        let mut res = self.clone();
+        res.opposite_inplace()?;
+        Ok(res)
+    }
+
+    /// Compute the opposite of all the LWE ciphertexts in the structure
+    /// Nothing is modified if one of the encoders is invalid
+    ///
+    /// # Output
+    /// * InvalidEncoderError - if one of the encoders is invalid
+    pub fn opposite_inplace(&mut self) -> PyResult<()> {
+        if let Some(i) = self.data.encoders.iter().position(|e| !e.is_valid()) {
+            return Err(PyValueError::new_err(format!(
+                "invalid encoder at index {}", i)));
+        }
+        for n in 0..self.data.nb_ciphertexts {
+            translate_error!(self.data.opposite_nth_inplace(n))?;
+        }
+        Ok(())
+    }
+
+    pub fn __neg__(&self) -> PyResult<VectorLWE> {
+        self.opposite()
+    }
+
+    pub fn __iadd__(&mut self, o: &PyAny) -> PyResult<()> {
+        if let Ok(cell) = o.downcast::<PyCell<VectorLWE>>() {
+            // the operand is self when it is already mutably borrowed (e.g. v += v)
+            let ct = match cell.try_borrow() {
+                Ok(o) => o.data.clone(),
+                Err(_) => self.data.clone(),
+            };
+            translate_error!(self.data.add_with_padding_inplace(&ct))
+        } else if let Ok(o) = o.extract::<Vec<f64>>() {
+            translate_error!(self.data.add_constant_dynamic_encoder_inplace(&o))
+        } else {
+            Err(PyTypeError::new_err("unsupported type for VectorLWE addition"))
+        }
+    }
+
+    pub fn __isub__(&mut self, o: &PyAny) -> PyResult<()> {
+        if let Ok(cell) = o.downcast::<PyCell<VectorLWE>>() {
+            // the operand is self when it is already mutably borrowed (e.g. v += v)
+            let ct = match cell.try_borrow() {
+                Ok(o) => o.data.clone(),
+                Err(_) => self.data.clone(),
+            };
+            translate_error!(self.data.sub_with_padding_inplace(&ct))
+        } else if let Ok(o) = o.extract::<Vec<f64>>() {
+            let o: Vec<f64> = o.iter().map(|m| -m).collect();
+            translate_error!(self.data.add_constant_dynamic_encoder_inplace(&o))
+        } else {
+            Err(PyTypeError::new_err("unsupported type for VectorLWE subtraction"))
+        }
+    }
+
+    /// Scale the decoded values of every ciphertext by a positive factor
+    /// Only the encoders (offset and delta) are rescaled: this changes how the
+    /// ciphertexts are interpreted, the bodies, masks and padding are untouched
+    ///
+    /// # Argument
+    /// * `factor` - the scaling factor, must be strictly positive
+    ///
+    /// # Output
+    /// * a new VectorLWE whose decryptions are multiplied by `factor`
+    pub fn scale_decoding(&self, factor: f64) -> PyResult<crate::VectorLWE> {
+        if factor <= 0. || !factor.is_finite() {
+            return Err(PyValueError::new_err(format!(
+                "factor must be a positive finite number, got {}", factor)));
+        }
+        let mut res = self.clone();
+        for encoder in res.data.encoders.iter_mut() {
+            encoder.o *= factor;
+            encoder.delta *= factor;
+        }
+        Ok(res)
+    }
+
     /// Compute a key switching operation on every ciphertext from the VectorLWE struct self
     ///
     /// # Argument
@@ -1230,10 +1882,11 @@ impl VectorLWE {
     /// ```
     pub fn keyswitch(&self, ksk: &crate::LWEKSK) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.keyswitch(&ksk.data))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: self.shape })
     }
 
     /// Compute a bootstrap on the n-th LWE from the self VectorLWE structure
+    /// The identity lookup table is cached on the bootstrapping key for the encoder of the n-th LWE
     ///
     /// # Argument
     /// * `bsk` - the bootstrapping key
@@ -1286,8 +1939,73 @@ impl VectorLWE {
         bsk: &crate::LWEBSK,
         n: usize,
     ) -> PyResult<crate::VectorLWE> {
-        let data = translate_error!(self.data.bootstrap_nth(&bsk.data, n))?;
-        Ok(VectorLWE{ data })
+        let ct = VectorLWE::nth_as_lwe(&self.data, n)?;
+        let table = translate_error!(bsk.lut_cache.identity_table(&bsk.data, &ct.encoder, &ct.encoder))?;
+        let res = translate_error!(crate::LWEBSK::bootstrap_with_table(&bsk.data, &ct, &table, &ct.encoder))?;
+        let data = VectorLWE::from_lwes(&[res])?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Bootstrap all the ciphertexts of the structure in parallel, with the GIL released
+    /// Each worker thread holds its own copy of the bootstrapping key
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE with all the ciphertexts bootstrapped
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    #[args(max_threads = "None", chunk_size = "None")]
+    pub fn bootstrap_all(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        let data = self.bootstrap_all_with_function(py, bsk, |x| x, None, Batch{ max_threads, chunk_size })?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: self.shape })
+    }
+
+    /// Reduce the noise of all the ciphertexts: key switch them to the input key of the
+    /// bootstrapping key, then bootstrap the identity in parallel, with the GIL released
+    ///
+    /// # Argument
+    /// * `ksk` - a key switching key from the key of self to the input key of `bsk`
+    /// * `bsk` - the bootstrapping key
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE under the output key of `bsk`
+    /// * DimensionError - if the keys do not chain from the dimension of self
+    #[args(max_threads = "None", chunk_size = "None")]
+    pub fn refresh_all(
+        &self,
+        py: Python,
+        ksk: &crate::LWEKSK,
+        bsk: &crate::LWEBSK,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        if ksk.data.dimension_before != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the key switching key expects dimension {} but the ciphertexts have dimension {}",
+                ksk.data.dimension_before, self.data.dimension)));
+        }
+        if ksk.data.dimension_after != bsk.data.get_lwe_dimension() {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the key switching key outputs dimension {} but the bootstrapping key expects {}",
+                ksk.data.dimension_after, bsk.data.get_lwe_dimension())));
+        }
+        let data = &self.data;
+        let ksk = &ksk.data;
+        let switched = translate_error!(py.allow_threads(move || data.keyswitch(ksk)))?;
+        let data = VectorLWE{ data: switched, key_fingerprint: None, shape: None }
+            .bootstrap_all_with_function(py, bsk, |x| x, None, Batch{ max_threads, chunk_size })?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: self.shape })
     }
 
     /// Compute a bootstrap and apply an arbitrary function to the given VectorLWE ciphertext
@@ -1354,7 +2072,221 @@ impl VectorLWE {
         let fun = |x| f.call1((x,)).unwrap().extract::<f64>().unwrap();
         let data = translate_error!(self.data.bootstrap_nth_with_function(
             &bsk.data, fun, &encoder_output.data, n))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Apply a Python function to all the ciphertexts with bootstraps computed in parallel
+    /// The lookup table is built once for each distinct input encoder while holding the GIL,
+    /// then the GIL is released and all the bootstraps share the tables
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `f` - a Python function taking and returning a float
+    /// * `encoder_output` - the encoder of the outputs
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE under the output key of `bsk`
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    #[args(max_threads = "None", chunk_size = "None")]
+    pub fn map(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        f: &PyFunction,
+        encoder_output: &crate::Encoder,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        self.map_with(py, bsk, |x| f.call1((x,))?.extract(), &encoder_output.data, Batch{ max_threads, chunk_size })
+    }
+
+    /// Apply f(scale * x + shift) to all the ciphertexts, the affine transform being folded
+    /// into the lookup table so each ciphertext costs a single bootstrap
+    /// As with map, the tables are built once for each distinct input encoder and the
+    /// bootstraps are computed in parallel
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `scale` - the factor applied to the messages before f
+    /// * `shift` - the constant added to the scaled messages before f
+    /// * `f` - a Python function taking and returning a float
+    /// * `encoder_output` - the encoder of the outputs
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE under the output key of `bsk`
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    #[args(max_threads = "None", chunk_size = "None")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn affine_activation(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        scale: f64,
+        shift: f64,
+        f: &PyFunction,
+        encoder_output: &crate::Encoder,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        self.map_with(
+            py, bsk, |x| f.call1((scale * x + shift,))?.extract(), &encoder_output.data,
+            Batch{ max_threads, chunk_size })
+    }
+
+    /// Bootstrap each ciphertext with a function that also depends on its position
+    /// f(i, x) is evaluated while holding the GIL to build one lookup table per ciphertext,
+    /// then the GIL is released and the bootstraps are computed in parallel
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `f` - a Python function taking an index and a float and returning a float
+    /// * `encoders_output` - a list with the output encoder of each ciphertext
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE under the output key of `bsk`
+    /// * ValueError - if there is not one output encoder per ciphertext
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    #[args(max_threads = "None", chunk_size = "None")]
+    pub fn bootstrap_indexed(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        f: &PyFunction,
+        encoders_output: &PyList,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        if encoders_output.len() != nb {
+            return Err(PyValueError::new_err(format!(
+                "{} output encoders given for {} ciphertexts", encoders_output.len(), nb)));
+        }
+        let encoders_output = encoders_output.iter().map(|e| Ok(e.extract::<crate::Encoder>()?.data))
+            .collect::<PyResult<Vec<_>>>()?;
+        let mut tables: Vec<Vec<Torus>> = Vec::with_capacity(nb);
+        for (i, (encoder, encoder_output)) in self.data.encoders.iter().zip(encoders_output.iter()).enumerate() {
+            let error = std::cell::RefCell::new(None);
+            let table = bsk.data.generate_functional_look_up_table(
+                encoder, encoder_output, |x| match f.call1((i, x)).and_then(|y| y.extract()) {
+                    Ok(y) => y,
+                    Err(e) => {
+                        error.borrow_mut().get_or_insert(e);
+                        0.
+                    }
+                });
+            if let Some(e) = error.into_inner() {
+                return Err(e);
+            }
+            tables.push(translate_error!(table)?);
+        }
+        let table_of: Vec<usize> = (0..nb).collect();
+        self.bootstrap_with_tables(py, bsk, &tables, &table_of, &encoders_output, Batch{ max_threads, chunk_size })
+    }
+
+    /// Round the n-th LWE ciphertext to the closest integer with a bootstrap
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `n` - the index of the ciphertext to round
+    /// * `encoder_output` - the encoder of the output, which should cover the rounded values
+    ///
+    /// # Output
+    /// * a new VectorLWE containing only the rounded ciphertext
+    /// * IndexError - if the requested ciphertext does not exist
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn round_nth(
+        &self, bsk: &crate::LWEBSK, n: usize, encoder_output: &crate::Encoder,
+    ) -> PyResult<crate::VectorLWE> {
+        let data = translate_error!(self.data.bootstrap_nth_with_function(
+            &bsk.data, |x| x.round(), &encoder_output.data, n))?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Round all the LWE ciphertexts to the closest integer with bootstraps run in parallel
+    /// (see bootstrap_all)
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `encoder_output` - the encoder of the outputs, which should cover the rounded values
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE with all the ciphertexts rounded
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    #[args(max_threads = "None", chunk_size = "None")]
+    pub fn round_all(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        encoder_output: &crate::Encoder,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        let data = self.bootstrap_all_with_function(
+            py, bsk, |x| x.round(), Some(&encoder_output.data), Batch{ max_threads, chunk_size })?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: self.shape })
+    }
+
+    /// Select element-wise between two VectorLWE: the i-th output is a[i] if selectors[i]
+    /// encrypts 1 and b[i] if it encrypts 0 (see LWE.select), computed in parallel
+    ///
+    /// # Arguments
+    /// * `selectors` - a VectorLWE encrypting 0 or 1 values
+    /// * `a` - the values returned where the selector is 1
+    /// * `b` - the values returned where the selector is 0
+    /// * `bsk` - the bootstrapping key
+    /// * `ksk` - the key switching key from the output key of `bsk` to its input key, only
+    /// optional when `bsk` maps the dimension of the inputs to itself
+    /// * `max_threads` - the maximum number of threads to use, all the cores by default
+    /// * `chunk_size` - the number of ciphertexts bootstrapped at a time to bound the peak memory, all by default
+    ///
+    /// # Output
+    /// * a new VectorLWE under the output key of `bsk`, without padding left
+    /// * ValueError - if the lengths differ or some inputs are not valid for LWE.select
+    #[staticmethod]
+    #[args(ksk = "None", max_threads = "None", chunk_size = "None")]
+    #[allow(clippy::too_many_arguments)]
+    pub fn cmux(
+        py: Python,
+        selectors: &crate::VectorLWE,
+        a: &crate::VectorLWE,
+        b: &crate::VectorLWE,
+        bsk: &crate::LWEBSK,
+        ksk: Option<&crate::LWEKSK>,
+        max_threads: Option<usize>,
+        chunk_size: Option<usize>,
+    ) -> PyResult<crate::VectorLWE> {
+        let nb = selectors.data.nb_ciphertexts;
+        if a.data.nb_ciphertexts != nb || b.data.nb_ciphertexts != nb {
+            return Err(PyValueError::new_err(format!(
+                "length mismatch: {} selectors, {} and {} values",
+                nb, a.data.nb_ciphertexts, b.data.nb_ciphertexts)));
+        }
+        let ksk = ksk.map(|k| &k.data);
+        let mut inputs = Vec::with_capacity(nb);
+        for i in 0..nb {
+            let (s, x, y) = (
+                VectorLWE::nth_as_lwe(&selectors.data, i)?,
+                VectorLWE::nth_as_lwe(&a.data, i)?,
+                VectorLWE::nth_as_lwe(&b.data, i)?,
+            );
+            crate::LWE::check_select(&s, &x, &y, &bsk.data, ksk)?;
+            inputs.push((s, x, y));
+        }
+        let inputs = &inputs;
+        let batch = Batch{ max_threads, chunk_size };
+        let data = VectorLWE::parallel_bootstrap(py, bsk, nb, batch, |key, i| {
+            let (s, x, y) = &inputs[i];
+            crate::LWE::select_unchecked(s, x, y, key, ksk)
+        })?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
     }
 
     /// Multiply two LWE ciphertexts thanks to two bootstrapping procedures
@@ -1424,7 +2356,7 @@ impl VectorLWE {
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.mul_from_bootstrap_nth(
             &ct.data, &bsk.data, n_self, n_ct))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
     }
 
     /// Return the size of one LWE ciphertext with the parameters of self
@@ -1435,10 +2367,89 @@ impl VectorLWE {
         self.data.dimension + 1
     }
 
+    /// Return the size in bytes of one LWE ciphertext with the parameters of self
+    pub fn ciphertext_size_bytes(&self) -> usize {
+        (self.data.dimension + 1) * std::mem::size_of::<Torus>()
+    }
+
+    /// Return the size in bytes of all the LWE ciphertexts of self (masks and bodies only)
+    pub fn total_size_bytes(&self) -> usize {
+        self.ciphertext_size_bytes() * self.data.nb_ciphertexts
+    }
+
     pub fn pp(&self) {
         self.data.pp();
     }
 
+    /// Return the smallest number of bits of precision left across all the ciphertexts,
+    /// i.e. the precision of the weakest ciphertext (0 if there is no ciphertext)
+    /// The precision left by a ciphertext is the one of its encoder, reduced by the bits the
+    /// noise of its variance overwrites (see pp)
+    pub fn min_noise_budget_bits(&self) -> usize {
+        self.noise_budgets().into_iter().min().unwrap_or(0)
+    }
+
+    /// Raise an error if some ciphertext has less than `bits` bits of precision left
+    ///
+    /// # Arguments
+    /// * `bits` - the minimum number of bits of precision required
+    pub fn assert_budget(&self, bits: usize) -> PyResult<()> {
+        let budgets = self.noise_budgets();
+        if let Some(i) = budgets.iter().position(|b| *b < bits) {
+            return Err(PyValueError::new_err(format!(
+                "ciphertext {} has {} bit(s) of precision left, {} required",
+                i, budgets[i], bits)));
+        }
+        Ok(())
+    }
+
+    /// Predict the variances add_with_padding(other) would produce, without computing it
+    ///
+    /// # Arguments
+    /// * `other` - the VectorLWE that would be added to self
+    /// # Output
+    /// * the variance of each output ciphertext
+    /// * ValueError - if self and other do not have the same number of ciphertexts
+    pub fn predict_add_variance(&self, other: &VectorLWE) -> PyResult<Vec<f64>> {
+        if self.data.nb_ciphertexts != other.data.nb_ciphertexts {
+            return Err(PyValueError::new_err(format!(
+                "the vectors have {} and {} ciphertexts",
+                self.data.nb_ciphertexts, other.data.nb_ciphertexts)));
+        }
+        Ok(self.data.variances.iter().zip(other.data.variances.iter())
+            .map(|(a, b)| npe::add_ciphertexts(*a, *b))
+            .collect())
+    }
+
+    /// Predict the variance of the output of a bootstrap of any ciphertext of self with `bsk`,
+    /// which does not depend on the input variance
+    ///
+    /// # Arguments
+    /// * `bsk` - the bootstrapping key
+    /// # Output
+    /// * the variance of a bootstrapped ciphertext
+    /// * DimensionError - if the bootstrapping key and the ciphertexts have incompatible dimensions
+    pub fn predict_bootstrap_variance(&self, bsk: &crate::LWEBSK) -> PyResult<f64> {
+        if self.data.dimension != bsk.data.get_lwe_dimension() {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the bootstrapping key expects dimension {} but the ciphertexts have dimension {}",
+                bsk.data.get_lwe_dimension(), self.data.dimension)));
+        }
+        Ok(<Torus as npe::cross::Cross>::bootstrap(
+            self.data.dimension, bsk.data.dimension, bsk.data.level, bsk.data.base_log,
+            bsk.data.polynomial_size, bsk.data.variance))
+    }
+
+    /// Return the number of bits of padding left in each ciphertext
+    pub fn padding_bits(&self) -> Vec<usize> {
+        self.data.encoders.iter().map(|e| e.nb_bit_padding).collect()
+    }
+
+    /// Return true if every ciphertext has at least `nb` bits of padding left
+    pub fn has_padding(&self, nb: usize) -> bool {
+        self.data.encoders.iter().all(|e| e.nb_bit_padding >= nb)
+    }
+
     /// Sum all the LWE ciphertexts contained in self into one single ciphertext and output it as a new VectorLWE
     ///
     /// # Output
@@ -1475,7 +2486,7 @@ impl VectorLWE {
     /// ```
     pub fn sum_with_padding(&self) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.sum_with_padding())?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
     }
 
     /// Sum all the LWE ciphertexts contained in self into one single ciphertext and output it as a
@@ -1519,17 +2530,657 @@ impl VectorLWE {
     /// ```
     pub fn sum_with_new_min(&self, new_min: f64) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.sum_with_new_min(new_min))?;
-        Ok(VectorLWE{ data })
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Sum a list of single-ciphertext VectorLWE into one single ciphertext without building the
+    /// concatenated VectorLWE first. As with sum_with_new_min, the output encoder has the size of
+    /// the input ones so we need to provide its min
+    ///
+    /// # Input
+    /// * `parts` - a list of VectorLWE with one ciphertext each, the same dimension and the same delta
+    /// * `new_min` - an f64 containing the min of the output encoder
+    ///
+    /// # Output
+    /// * A new VectorLWE containing only one ciphertext
+    /// * ValueError if the list is empty or the parts are not compatible
+    #[staticmethod]
+    pub fn sum_of(parts: &PyList, new_min: f64) -> PyResult<crate::VectorLWE> {
+        let parts = parts.iter().map(|x| x.extract::<crate::VectorLWE>())
+            .collect::<PyResult<Vec<_>>>()?;
+        let first = match parts.first() {
+            Some(first) => &first.data,
+            None => return Err(PyValueError::new_err("no ciphertext to sum")),
+        };
+        for (i, part) in parts.iter().enumerate() {
+            if part.data.nb_ciphertexts != 1 {
+                return Err(PyValueError::new_err(format!(
+                    "part {} has {} ciphertexts, expected 1", i, part.data.nb_ciphertexts)));
+            } else if part.data.dimension != first.dimension {
+                return Err(PyValueError::new_err(format!(
+                    "part {} has dimension {}, expected {}", i, part.data.dimension, first.dimension)));
+            } else if !helper_deltas_eq(part.data.encoders[0].delta, first.encoders[0].delta) {
+                return Err(PyValueError::new_err(format!(
+                    "part {} has delta {}, expected {}",
+                    i, part.data.encoders[0].delta, first.encoders[0].delta)));
+            }
+        }
+        let mut data = first.clone();
+        for part in parts[1..].iter() {
+            data.ciphertexts.as_mut_tensor().update_with_wrapping_add(part.data.ciphertexts.as_tensor());
+        }
+        // correct the body so that it decodes with the new min
+        let mut encoder = first.encoders[0].clone();
+        encoder.o = new_min;
+        let sum_min: f64 = parts.iter().map(|p| p.data.encoders[0].o).sum();
+        let correction = translate_error!(encoder.encode_outside_interval_operators(sum_min))?;
+        let body = data.ciphertexts.as_mut_tensor().get_element_mut(data.dimension);
+        *body = body.wrapping_add(correction);
+        let variances: Vec<f64> = parts.iter().map(|p| p.data.variances[0]).collect();
+        let variance = npe::add_several_ciphertexts(&variances);
+        translate_error!(encoder.update_precision_from_variance(variance))?;
+        data.variances = vec![variance];
+        data.encoders = vec![encoder];
+        Ok(VectorLWE{ data, key_fingerprint: parts[0].key_fingerprint, shape: None })
+    }
+
+    /// Sum each group of `group_size` consecutive ciphertexts into one ciphertext (as
+    /// sum_with_padding, eating ceil(log2(group_size)) bits of padding), then move the min of
+    /// the output encoder of the k-th group to new_mins[k] without changing its size
+    ///
+    /// # Input
+    /// * `group_size` - the number of ciphertexts summed together, dividing nb_ciphertexts
+    /// * `new_mins` - the min of the output encoder of each group
+    ///
+    /// # Output
+    /// * A new VectorLWE containing nb_ciphertexts / group_size ciphertexts
+    /// * ValueError if the groups do not split self, there is not one min per group or
+    ///   some ciphertexts do not have enough padding
+    pub fn group_sum_with_padding(&self, group_size: usize, new_mins: Vec<f64>) -> PyResult<crate::VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        if group_size == 0 || !nb.is_multiple_of(group_size) {
+            return Err(PyValueError::new_err(format!(
+                "cannot split {} ciphertexts into groups of {}", nb, group_size)));
+        }
+        if new_mins.len() != nb / group_size {
+            return Err(PyValueError::new_err(format!(
+                "{} new mins given for {} groups", new_mins.len(), nb / group_size)));
+        }
+        let consumed = (usize::BITS - (group_size - 1).leading_zeros()) as usize;
+        if let Some(i) = self.data.encoders.iter().position(|e| e.nb_bit_padding < consumed) {
+            return Err(PyValueError::new_err(format!(
+                "ciphertext {} has {} bits of padding but {} are needed to sum groups of {}",
+                i, self.data.encoders[i].nb_bit_padding, consumed, group_size)));
+        }
+        let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, new_mins.len()))?;
+        for (k, (group, new_min)) in self.rechunk(group_size)?.iter().zip(new_mins).enumerate() {
+            let mut sum = translate_error!(group.data.sum_with_padding())?;
+            // correct the body so that it decodes with the new min
+            let mut encoder = sum.encoders[0].clone();
+            encoder.o = new_min;
+            let correction = translate_error!(encoder.encode_outside_interval_operators(sum.encoders[0].o))?;
+            let body = sum.ciphertexts.as_mut_tensor().get_element_mut(sum.dimension);
+            *body = body.wrapping_add(correction);
+            sum.encoders[0] = encoder;
+            translate_error!(data.copy_in_nth_nth_inplace(k, &sum, 0))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Count the ciphertexts whose message is not zero, as an encrypted count
+    /// Each ciphertext is bootstrapped into an indicator (1 if |x| > zero_tol, else 0) in
+    /// parallel, which costs one bootstrap per ciphertext, then the indicators are summed
+    /// The output encoder is [0, nb_ciphertexts] with one unit of granularity, so the count
+    /// may lose precision when the bootstrap cannot hold that many bits
+    ///
+    /// # Input
+    /// * `bsk` - the bootstrapping key
+    /// * `zero_tol` - the largest absolute value counted as zero
+    ///
+    /// # Output
+    /// * A new VectorLWE containing only one ciphertext, under the output key of `bsk`
+    /// * DimensionError - if the bootstrapping key and the input ciphertext have incompatible dimensions
+    pub fn count_nonzero(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        zero_tol: f64,
+    ) -> PyResult<crate::VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        let precision = (usize::BITS - nb.leading_zeros()) as usize;
+        let encoder = translate_error!(concrete::Encoder::new(0., nb as f64, precision, 0))?;
+        let indicators = self.bootstrap_all_with_function(
+            py, bsk, |x| if x.abs() > zero_tol { 1. } else { 0. }, Some(&encoder), Batch::default())?;
+        let data = translate_error!(indicators.sum_with_new_min(0.))?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Approximately divide every ciphertext by the sum of all the ciphertexts, as in a softmax
+    /// The sum s is computed with sum_with_padding, a scaled reciprocal c / s is bootstrapped and
+    /// key switched back to the input key, then each x_i is multiplied by it with
+    /// mul_from_bootstrap_nth and the factor c is removed from the output encoders
+    ///
+    /// Constraints:
+    /// * all the encoders must have the same delta and padding and a non negative min, so that s > 0
+    /// * the reciprocal table covers [g, max] where g is the granularity of the sum encoder,
+    ///   sums below g are clamped to g
+    /// * the reciprocal is encoded with the precision of the inputs, so the error grows
+    ///   with 1 / s^2 and the result is only accurate when s is far from g
+    /// * ceil(log2(nb_ciphertexts)) + 1 bits of padding and 2 bits of precision are needed
+    ///
+    /// # Input
+    /// * `bsk` - the bootstrapping key, from the input key to its output key
+    /// * `ksk` - a key switching key from the output key of `bsk` back to the input key
+    ///
+    /// # Output
+    /// * A new VectorLWE containing the x_i / s, under the output key of `bsk`
+    /// * ValueError - if the encoders do not satisfy the constraints above
+    /// * NotEnoughPaddingError - if there is not enough padding
+    pub fn normalize_by_cleartext_sum(
+        &self,
+        bsk: &crate::LWEBSK,
+        ksk: &crate::LWEKSK,
+    ) -> PyResult<crate::VectorLWE> {
+        let encoder = &self.data.encoders[0];
+        if self.data.encoders.iter().any(|e| {
+            !helper_deltas_eq(e.delta, encoder.delta) || e.nb_bit_padding != encoder.nb_bit_padding || e.o < 0.
+        }) {
+            return Err(PyValueError::new_err(
+                "the encoders must share the same delta and padding and have a non negative min"));
+        }
+        let sum = translate_error!(self.data.sum_with_padding())?;
+        let (sum_min, sum_max) = (sum.encoders[0].o, sum.encoders[0].o + sum.encoders[0].delta);
+        let floor = sum_min.max(sum.encoders[0].get_granularity());
+        // c / s spans exactly the interval of an encoder with the delta of the inputs
+        let precision = encoder.nb_bit_precision;
+        let c = encoder.delta * (1. - f64::powi(2., -(precision as i32))) / (1. / floor - 1. / sum_max);
+        let reciprocal_encoder = concrete::Encoder {
+            o: c / sum_max,
+            delta: encoder.delta,
+            nb_bit_precision: precision,
+            nb_bit_padding: encoder.nb_bit_padding,
+            round: false,
+        };
+        let reciprocal = translate_error!(sum.bootstrap_nth_with_function(
+            &bsk.data, |s| c / s.max(floor), &reciprocal_encoder, 0))?;
+        let reciprocal = translate_error!(reciprocal.keyswitch(&ksk.data))?;
+
+        let mut data = translate_error!(concrete::VectorLWE::zero(
+            bsk.data.polynomial_size * bsk.data.dimension, self.data.nb_ciphertexts))?;
+        for i in 0..self.data.nb_ciphertexts {
+            let product = translate_error!(self.data.mul_from_bootstrap_nth(&reciprocal, &bsk.data, i, 0))?;
+            translate_error!(data.copy_in_nth_nth_inplace(i, &product, 0))?;
+        }
+        for encoder in data.encoders.iter_mut() {
+            encoder.o /= c;
+            encoder.delta /= c;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Compute the affine combination sum_i weights[i] * x_i + bias of all the LWE ciphertexts
+    /// contained in self and output it as a new VectorLWE with one single ciphertext.
+    /// The weights are applied with mul_constant_with_padding, the products are summed with
+    /// sum_with_new_min and the bias is added with add_constant_dynamic_encoder
+    ///
+    /// # Input
+    /// * `weights` - a list of f64, one per ciphertext
+    /// * `bias` - an f64 added to the weighted sum
+    /// * `max_weight` - a positive f64 greater than any of the weights.abs()
+    /// * `nb_bit_padding` - the number of bits of padding to be consumed by the multiplication
+    /// * `new_min` - an f64 containing the min of the encoder of the weighted sum (before the bias)
+    ///
+    /// # Output
+    /// * A new VectorLWE containing only one ciphertext
+    /// * ValueError if the number of weights differs from the number of ciphertexts
+    /// * ConstantMaximumError - if one element of `weights` if bigger than `max_weight`
+    /// * ZeroInIntervalError - if zero is not in the interval described by the encoders
+    /// * NotEnoughPaddingError - if there is not enough padding
+    pub fn linear_with_padding(
+        &self,
+        weights: Vec<f64>,
+        bias: f64,
+        max_weight: f64,
+        nb_bit_padding: usize,
+        new_min: f64,
+    ) -> PyResult<crate::VectorLWE> {
+        if weights.len() != self.data.nb_ciphertexts {
+            return Err(PyValueError::new_err(format!(
+                "expected {} weights, got {}", self.data.nb_ciphertexts, weights.len())));
+        }
+        let products = translate_error!(self.data.mul_constant_with_padding(
+            &weights, max_weight, nb_bit_padding))?;
+        let sum = translate_error!(products.sum_with_new_min(new_min))?;
+        let data = translate_error!(sum.add_constant_dynamic_encoder(&[bias]))?;
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Compute the argmax of the LWE ciphertexts as an encrypted one-hot vector: the position
+    /// of the maximum decrypts to ~1. and the others to ~0. (several positions can be set when
+    /// the largest values are closer than the granularity of the encoder)
+    /// The maximum is first computed with n-1 bootstrapped max, then each ciphertext is compared
+    /// to it with one more bootstrap, hence 2n-1 bootstraps in total
+    /// All the ciphertexts must share the same encoder with at least 2 bits of padding, and the
+    /// bootstrapping key must output ciphertexts under its own input key (i.e. the LWE key is
+    /// derived from the RLWE key) since bootstrapped values are combined with the inputs
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    ///
+    /// # Output
+    /// * a new VectorLWE with the one-hot values, encoded in [0,1] with the padding of the input
+    /// * ValueError - if the encoders differ, the padding is too small or the bsk does not fit
+    pub fn argmax_onehot(&self, bsk: &crate::LWEBSK) -> PyResult<crate::VectorLWE> {
+        self.check_max_preconditions(bsk)?;
+        let encoder = &self.data.encoders[0];
+        let mut max = translate_error!(self.data.extract_nth(0))?;
+        for i in 1..self.data.nb_ciphertexts {
+            let ct = translate_error!(self.data.extract_nth(i))?;
+            max = self.max_with_bootstrap(&max, &ct, bsk)?;
+        }
+        let threshold = -encoder.get_granularity() / 2.;
+        let encoder_output = translate_error!(concrete::Encoder::new(
+            0., 1., 2, encoder.nb_bit_padding))?;
+        let mut data = translate_error!(concrete::VectorLWE::zero(
+            self.data.dimension, self.data.nb_ciphertexts))?;
+        for i in 0..self.data.nb_ciphertexts {
+            let ct = translate_error!(self.data.extract_nth(i))?;
+            let diff = Self::sub_keeping_granularity(&ct, &max)?;
+            let onehot = translate_error!(diff.bootstrap_nth_with_function(
+                &bsk.data, |x| if x > threshold { 1. } else { 0. }, &encoder_output, 0))?;
+            translate_error!(data.copy_in_nth_nth_inplace(i, &onehot, 0))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Compute the running maximum of the LWE ciphertexts: out[i] = max(in[0..=i])
+    /// Each step is a bootstrapped max (see argmax_onehot), hence n-1 bootstraps
+    /// All the ciphertexts must share the same encoder with at least 2 bits of padding, and the
+    /// bootstrapping key must output ciphertexts under its own input key
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    ///
+    /// # Output
+    /// * a new VectorLWE with the running maxima, encoded like the input
+    /// * ValueError - if the encoders differ, the padding is too small or the bsk does not fit
+    pub fn running_max_bootstrap(&self, bsk: &crate::LWEBSK) -> PyResult<crate::VectorLWE> {
+        self.check_max_preconditions(bsk)?;
+        let mut data = self.data.clone();
+        let mut max = translate_error!(self.data.extract_nth(0))?;
+        for i in 1..self.data.nb_ciphertexts {
+            let ct = translate_error!(self.data.extract_nth(i))?;
+            max = self.max_with_bootstrap(&max, &ct, bsk)?;
+            translate_error!(data.copy_in_nth_nth_inplace(i, &max, 0))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Compute an encrypted histogram of the LWE ciphertexts over cleartext bin edges
+    /// For each value and each bin, an indicator of the bin is computed with a bootstrap and the
+    /// indicators of a bin are summed, hence nb_ciphertexts * (edges.len() - 1) bootstraps
+    /// As in NumPy, the bins are half-open except the last one which includes its right edge
+    ///
+    /// # Argument
+    /// * `bsk` - the bootstrapping key
+    /// * `edges` - the increasing edges of the bins, at least 2
+    ///
+    /// # Output
+    /// * a new VectorLWE with one encrypted count per bin, encoded in [0, nb_ciphertexts]
+    /// * ValueError - if the edges are not increasing or fewer than 2
+    pub fn histogram(&self, bsk: &crate::LWEBSK, edges: Vec<f64>) -> PyResult<crate::VectorLWE> {
+        if edges.len() < 2 || edges.windows(2).any(|w| w[0] >= w[1]) {
+            return Err(PyValueError::new_err("edges must be increasing and at least 2"));
+        }
+        let nb = self.data.nb_ciphertexts;
+        let nb_bins = edges.len() - 1;
+        let encoder = &self.data.encoders[0];
+        let nb_bit_count = (usize::BITS - nb.leading_zeros()) as usize;
+        let encoder_output = translate_error!(concrete::Encoder::new(
+            0., nb as f64, encoder.nb_bit_precision.max(nb_bit_count), encoder.nb_bit_padding))?;
+        let dimension = bsk.data.dimension * bsk.data.polynomial_size;
+        let mut data = translate_error!(concrete::VectorLWE::zero(dimension, nb_bins))?;
+        for b in 0..nb_bins {
+            let (low, high) = (edges[b], edges[b + 1]);
+            let last = b + 1 == nb_bins;
+            let indicator = |x: f64| {
+                if low <= x && (x < high || (last && x <= high)) { 1. } else { 0. }
+            };
+            let mut count = translate_error!(self.data.bootstrap_nth_with_function(
+                &bsk.data, indicator, &encoder_output, 0))?;
+            for j in 1..nb {
+                let ct = translate_error!(self.data.bootstrap_nth_with_function(
+                    &bsk.data, indicator, &encoder_output, j))?;
+                count = translate_error!(count.add_with_new_min(&ct, &[0.]))?;
+            }
+            translate_error!(data.copy_in_nth_nth_inplace(b, &count, 0))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
     }
-    
-    pub fn save(&self, path: &str) -> PyResult<()> {
-        translate_error!(self.data.save(path))
+
+    /// Reorder the LWE ciphertexts with a permutation: the i-th output is the perm[i]-th input
+    /// Encoders and variances follow their ciphertexts
+    ///
+    /// # Argument
+    /// * `perm` - a permutation of 0..nb_ciphertexts
+    ///
+    /// # Output
+    /// * a new VectorLWE
+    /// * ValueError - if `perm` is not a permutation of 0..nb_ciphertexts
+    pub fn permute(&self, perm: Vec<usize>) -> PyResult<crate::VectorLWE> {
+        self.check_permutation(&perm)?;
+        let mut data = self.data.clone();
+        for (i, p) in perm.iter().enumerate() {
+            translate_error!(data.copy_in_nth_nth_inplace(i, &self.data, *p))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Undo permute: the perm[i]-th output is the i-th input
+    ///
+    /// # Argument
+    /// * `perm` - a permutation of 0..nb_ciphertexts
+    ///
+    /// # Output
+    /// * a new VectorLWE
+    /// * ValueError - if `perm` is not a permutation of 0..nb_ciphertexts
+    pub fn inverse_permute(&self, perm: Vec<usize>) -> PyResult<crate::VectorLWE> {
+        self.check_permutation(&perm)?;
+        let mut data = self.data.clone();
+        for (i, p) in perm.iter().enumerate() {
+            translate_error!(data.copy_in_nth_nth_inplace(*p, &self.data, i))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Split channel-interleaved ciphertexts (c0_t0, c1_t0, ..., c0_t1, c1_t1, ...) into one
+    /// VectorLWE per channel, encoders and variances follow their ciphertexts
+    ///
+    /// # Argument
+    /// * `num_channels` - the number of channels, which has to divide nb_ciphertexts
+    ///
+    /// # Output
+    /// * a list of `num_channels` VectorLWE, the k-th one holding the ciphertexts of channel k
+    pub fn deinterleave(&self, num_channels: usize) -> PyResult<Vec<crate::VectorLWE>> {
+        let nb = self.data.nb_ciphertexts;
+        if num_channels == 0 || !nb.is_multiple_of(num_channels) {
+            return Err(PyValueError::new_err(format!(
+                "{} ciphertexts cannot be split into {} channels", nb, num_channels)));
+        }
+        let length = nb / num_channels;
+        let mut res = Vec::with_capacity(num_channels);
+        for k in 0..num_channels {
+            let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, length))?;
+            for i in 0..length {
+                translate_error!(data.copy_in_nth_nth_inplace(i, &self.data, i * num_channels + k))?;
+            }
+            res.push(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None });
+        }
+        Ok(res)
+    }
+
+    /// Interleave channels into one VectorLWE (inverse of deinterleave)
+    ///
+    /// # Argument
+    /// * `parts` - a list of VectorLWE, one per channel, with the same length and dimension
+    ///
+    /// # Output
+    /// * a new VectorLWE where the ciphertext i * len(parts) + k is the i-th one of channel k
+    #[staticmethod]
+    pub fn interleave(parts: &PyList) -> PyResult<crate::VectorLWE> {
+        let parts = parts.iter().map(|x| x.extract::<crate::VectorLWE>())
+            .collect::<PyResult<Vec<_>>>()?;
+        let first = match parts.first() {
+            Some(first) => &first.data,
+            None => return Err(PyValueError::new_err("no channel to interleave")),
+        };
+        for (k, part) in parts.iter().enumerate() {
+            if part.data.nb_ciphertexts != first.nb_ciphertexts {
+                return Err(PyValueError::new_err(format!(
+                    "channel {} has {} ciphertexts, expected {}",
+                    k, part.data.nb_ciphertexts, first.nb_ciphertexts)));
+            } else if part.data.dimension != first.dimension {
+                return Err(PyValueError::new_err(format!(
+                    "channel {} has dimension {}, expected {}", k, part.data.dimension, first.dimension)));
+            }
+        }
+        let num_channels = parts.len();
+        let mut data = translate_error!(concrete::VectorLWE::zero(
+            first.dimension, first.nb_ciphertexts * num_channels))?;
+        for (k, part) in parts.iter().enumerate() {
+            for i in 0..first.nb_ciphertexts {
+                translate_error!(data.copy_in_nth_nth_inplace(i * num_channels + k, &part.data, i))?;
+            }
+        }
+        Ok(VectorLWE{ data, key_fingerprint: parts[0].key_fingerprint, shape: None })
+    }
+
+    /// Split the ciphertexts into consecutive VectorLWE of `chunk_size` ciphertexts (the last one
+    /// may be smaller), encoders and variances follow their ciphertexts
+    ///
+    /// # Argument
+    /// * `chunk_size` - the number of ciphertexts per chunk, at least 1
+    ///
+    /// # Output
+    /// * a list of ceil(nb_ciphertexts / chunk_size) VectorLWE
+    pub fn rechunk(&self, chunk_size: usize) -> PyResult<Vec<crate::VectorLWE>> {
+        if chunk_size == 0 {
+            return Err(PyValueError::new_err("the chunk size must be at least 1"));
+        }
+        let size = self.data.get_ciphertext_size();
+        let cts = self.data.ciphertexts.as_tensor().as_slice();
+        let mut res = Vec::with_capacity(self.data.nb_ciphertexts.div_ceil(chunk_size));
+        for start in (0..self.data.nb_ciphertexts).step_by(chunk_size) {
+            let end = self.data.nb_ciphertexts.min(start + chunk_size);
+            let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, end - start))?;
+            data.ciphertexts.as_mut_tensor().as_mut_slice().copy_from_slice(&cts[start * size..end * size]);
+            data.variances = self.data.variances[start..end].to_vec();
+            data.encoders = self.data.encoders[start..end].to_vec();
+            res.push(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None });
+        }
+        Ok(res)
+    }
+
+    /// Concatenate several VectorLWE into one (inverse of rechunk)
+    ///
+    /// # Argument
+    /// * `parts` - a list of VectorLWE with the same dimension
+    ///
+    /// # Output
+    /// * a new VectorLWE with the ciphertexts of all the parts, in order
+    #[staticmethod]
+    pub fn concat(parts: &PyList) -> PyResult<crate::VectorLWE> {
+        let parts = parts.iter().map(|x| x.extract::<crate::VectorLWE>())
+            .collect::<PyResult<Vec<_>>>()?;
+        let first = match parts.first() {
+            Some(first) => &first.data,
+            None => return Err(PyValueError::new_err("no part to concatenate")),
+        };
+        if let Some(k) = parts.iter().position(|p| p.data.dimension != first.dimension) {
+            return Err(PyValueError::new_err(format!(
+                "part {} has dimension {}, expected {}", k, parts[k].data.dimension, first.dimension)));
+        }
+        let nb = parts.iter().map(|p| p.data.nb_ciphertexts).sum();
+        let mut data = translate_error!(concrete::VectorLWE::zero(first.dimension, nb))?;
+        let mut ciphertexts = Vec::with_capacity(nb * first.get_ciphertext_size());
+        data.variances.clear();
+        data.encoders.clear();
+        for part in parts.iter() {
+            ciphertexts.extend_from_slice(part.data.ciphertexts.as_tensor().as_slice());
+            data.variances.extend_from_slice(&part.data.variances);
+            data.encoders.extend_from_slice(&part.data.encoders);
+        }
+        data.ciphertexts.as_mut_tensor().as_mut_slice().copy_from_slice(&ciphertexts);
+        let key_fingerprint = parts[0].key_fingerprint
+            .filter(|f| parts.iter().all(|p| p.key_fingerprint == Some(*f)));
+        Ok(VectorLWE{ data, key_fingerprint, shape: None })
+    }
+
+    /// Check for each LWE ciphertext if it is trivial, i.e. its mask is all zeros so that its
+    /// body is the plaintext itself, readable without the secret key
+    pub fn is_trivial(&self) -> Vec<bool> {
+        let dimension = self.data.dimension;
+        self.data.ciphertexts.as_tensor().as_slice()
+            .chunks(self.data.get_ciphertext_size())
+            .map(|ct| ct[..dimension].iter().all(|x| *x == 0))
+            .collect()
+    }
+
+    /// Copy the raw Torus elements of the ciphertexts (masks followed by bodies) into a NumPy
+    /// array of shape (nb_ciphertexts, dimension + 1) and dtype uint64, for debugging
+    ///
+    /// # Output
+    /// * a new NumPy array
+    /// * ImportError if NumPy is not installed
+    pub fn raw_ciphertexts_numpy(&self, py: Python) -> PyResult<PyObject> {
+        let rows: Vec<Vec<Torus>> = self.data.ciphertexts.as_tensor().as_slice()
+            .chunks(self.data.get_ciphertext_size())
+            .map(|row| row.to_vec())
+            .collect();
+        let numpy = py.import("numpy")?;
+        let array = numpy.call_method1("array", (rows, numpy.getattr("uint64")?))?;
+        Ok(array.into())
+    }
+
+    /// Save the structure to a file, gzip compressed if `compress` is set
+    /// The fingerprint of the secret key is stored along the ciphertexts when it is known
+    #[args(compress = "false")]
+    pub fn save(&self, path: &str, compress: bool) -> PyResult<()> {
+        let bytes = VectorLWE::serialize(&self.data, self.key_fingerprint, compress)?;
+        translate_error!(std::fs::write(path, bytes))
     }
 
+    /// Load a structure saved with save, the compression is detected from the gzip header
     #[staticmethod]
     pub fn load(path: &str) -> PyResult<VectorLWE> {
-        let data = translate_error!(concrete::VectorLWE::load(path))?;
-        Ok(VectorLWE{ data })
+        let bytes = translate_error!(std::fs::read(path))?;
+        let (data, key_fingerprint) = VectorLWE::deserialize(&bytes)?;
+        Ok(VectorLWE{ data, key_fingerprint, shape: None })
+    }
+
+    /// Serialize each LWE ciphertext of the structure separately
+    ///
+    /// # Arguments
+    /// * `compress` - gzip each serialized ciphertext
+    ///
+    /// # Output
+    /// * a list where the i-th element is the serialized single-ciphertext VectorLWE
+    ///   (dimension, encoder, variance, mask and body) of the i-th ciphertext
+    #[args(compress = "false")]
+    pub fn to_bytes_per_ciphertext(&self, compress: bool) -> PyResult<Vec<Vec<u8>>> {
+        let mut res = Vec::with_capacity(self.data.nb_ciphertexts);
+        for i in 0..self.data.nb_ciphertexts {
+            let ct = translate_error!(self.data.extract_nth(i))?;
+            res.push(VectorLWE::serialize(&ct, self.key_fingerprint, compress)?);
+        }
+        Ok(res)
+    }
+
+    /// Rebuild a VectorLWE from the output of to_bytes_per_ciphertext, compressed or not
+    ///
+    /// # Arguments
+    /// * `chunks` - a list of serialized single-ciphertext VectorLWE, all with the same dimension
+    ///
+    /// # Output
+    /// * a new VectorLWE containing the ciphertexts in the order of `chunks`
+    /// * ValueError if a chunk cannot be deserialized, does not hold exactly one ciphertext,
+    ///   or has a different dimension than the first one
+    #[staticmethod]
+    pub fn from_bytes_per_ciphertext(chunks: Vec<Vec<u8>>) -> PyResult<VectorLWE> {
+        let mut cts = Vec::with_capacity(chunks.len());
+        let mut fingerprints = Vec::with_capacity(chunks.len());
+        for chunk in chunks.iter() {
+            let (ct, fingerprint) = VectorLWE::deserialize(chunk)?;
+            fingerprints.push(fingerprint);
+            if ct.nb_ciphertexts != 1 {
+                return Err(PyValueError::new_err(format!(
+                    "expected a single ciphertext per chunk, got {}", ct.nb_ciphertexts)));
+            }
+            cts.push(ct);
+        }
+        let dimension = match cts.first() {
+            Some(ct) => ct.dimension,
+            None => return Err(PyValueError::new_err("no ciphertext to rebuild from")),
+        };
+        let mut data = translate_error!(concrete::VectorLWE::zero(dimension, cts.len()))?;
+        for (i, ct) in cts.iter().enumerate() {
+            if ct.dimension != dimension {
+                return Err(PyValueError::new_err(format!(
+                    "dimension mismatch: chunk {} has {}, expected {}", i, ct.dimension, dimension)));
+            }
+            translate_error!(data.copy_in_nth_nth_inplace(i, ct, 0))?;
+        }
+        // the key is only known if all the chunks agree on it
+        let key_fingerprint = fingerprints[0].filter(|f| fingerprints.iter().all(|g| *g == Some(*f)));
+        Ok(VectorLWE{ data, key_fingerprint, shape: None })
+    }
+
+    /// Index the ciphertexts like a Python list: an integer (possibly negative) gives a VectorLWE
+    /// with that single ciphertext, a slice gives a VectorLWE with the selected ciphertexts
+    /// An empty selection raises an error since a VectorLWE holds at least one ciphertext
+    pub fn __getitem__(&self, index: &PyAny) -> PyResult<VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        let positions: Vec<usize> = if let Ok(slice) = index.downcast::<PySlice>() {
+            let indices = slice.indices(nb as std::os::raw::c_long)?;
+            (0..indices.slicelength)
+                .map(|i| (indices.start + i * indices.step) as usize)
+                .collect()
+        } else if let Ok(i) = index.extract::<isize>() {
+            let n = if i < 0 { i + nb as isize } else { i };
+            if n < 0 || n >= nb as isize {
+                return Err(PyIndexError::new_err(format!(
+                    "index {} out of range for {} ciphertexts", i, nb)));
+            }
+            vec![n as usize]
+        } else {
+            return Err(PyTypeError::new_err("VectorLWE indices must be integers or slices"));
+        };
+        let mut data = translate_error!(concrete::VectorLWE::zero(self.data.dimension, positions.len()))?;
+        for (i, p) in positions.iter().enumerate() {
+            translate_error!(data.copy_in_nth_nth_inplace(i, &self.data, *p))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Encrypted vector times cleartext matrix: the i-th output ciphertext is the sum over j of
+    /// weights[i][j] times the j-th ciphertext, computed with mul_constant_with_padding and
+    /// sum_with_padding, so the ciphertexts need enough padding for both
+    pub fn __matmul__(&self, o: &PyAny) -> PyResult<VectorLWE> {
+        let weights = match o.extract::<Vec<Vec<f64>>>() {
+            Ok(w) => w,
+            Err(_) => return Err(PyTypeError::new_err("unsupported type for VectorLWE matmul")),
+        };
+        if weights.is_empty() {
+            return Err(PyValueError::new_err("the matrix has no row"));
+        }
+        let mut data = translate_error!(concrete::VectorLWE::zero(
+            self.data.dimension, weights.len()))?;
+        for (i, row) in weights.iter().enumerate() {
+            let sum = self.dot_with_padding(row)?;
+            translate_error!(data.copy_in_nth_nth_inplace(i, &sum, 0))?;
+        }
+        Ok(VectorLWE{ data, key_fingerprint: self.key_fingerprint, shape: None })
+    }
+
+    /// Return false: a VectorLWE never holds secret key material, only the public ciphertexts,
+    /// their variances and encoders, its shape and the fingerprint of the encryption key (a
+    /// SHA-256 based digest from which the key cannot be recovered), so its pickle and its saved
+    /// files are safe to share.
+    /// This is an invariant of the type, any field added to it must keep it
+    pub fn contains_secret(&self) -> bool {
+        false
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<VectorLWE, _>(py, &(&self.data, self.key_fingerprint))
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<VectorLWE> {
+        let (data, key_fingerprint) = crate::helper_from_bytes(bytes)?;
+        Ok(VectorLWE{ data, key_fingerprint, shape: None })
     }
 
     pub fn __repr__(&self) -> String {
@@ -1537,6 +3188,407 @@ impl VectorLWE {
     }
 }
 
+impl VectorLWE {
+    /// Compare the fingerprint of `sk` with the one recorded at encryption, if any
+    /// `mode` is "warn" (emit a RuntimeWarning), "raise" (raise a ValueError) or "ignore"
+    fn check_key_fingerprint(&self, py: Python, sk: &crate::LWESecretKey, mode: &str) -> PyResult<()> {
+        if !["warn", "raise", "ignore"].contains(&mode) {
+            return Err(PyValueError::new_err(format!(
+                "key_check must be \"warn\", \"raise\" or \"ignore\", got {:?}", mode)));
+        }
+        let expected = match self.key_fingerprint {
+            Some(f) if mode != "ignore" => f,
+            _ => return Ok(()),
+        };
+        let actual = crate::LWESecretKey::fingerprint_of(&sk.data);
+        if actual == expected {
+            return Ok(());
+        }
+        let msg = format!(
+            "the ciphertexts were encrypted under the key {:016x} but are decrypted with the key {:016x}",
+            expected, actual);
+        if mode == "raise" {
+            return Err(PyValueError::new_err(msg));
+        }
+        let warning = py.import("builtins")?.getattr("RuntimeWarning")?;
+        PyErr::warn(py, warning, &msg, 1)
+    }
+
+    /// Decrypt and decode the ciphertexts in `range` in a single pass, the phase of each
+    /// ciphertext being computed in place and decoded with its encoder read by reference,
+    /// so that nothing is cloned or allocated per ciphertext
+    pub(crate) fn decrypt_decode_range(
+        &self, sk: &crate::LWESecretKey, range: std::ops::Range<usize>,
+    ) -> PyResult<Vec<f64>> {
+        if sk.data.dimension != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the ciphertexts have dimension {} but the secret key has dimension {}",
+                self.data.dimension, sk.data.dimension)));
+        }
+        let size = self.data.get_ciphertext_size();
+        let cts = &self.data.ciphertexts.as_tensor().as_slice()[range.start * size..range.end * size];
+        cts.chunks_exact(size).zip(self.data.encoders[range].iter()).map(|(ct, encoder)| {
+            // decrypt_lwe accumulates into its output
+            let mut phase = Plaintext(0);
+            sk.data.val.decrypt_lwe(&mut phase, &LweCiphertext::from_container(ct));
+            translate_error!(encoder.decode_single(phase.0))
+        }).collect()
+    }
+
+    /// Serialize a structure to JSON, gzip compressed if `compress` is set, the key fingerprint
+    /// being stored as an extra field
+    pub(crate) fn serialize(
+        data: &concrete::VectorLWE, key_fingerprint: Option<u64>, compress: bool,
+    ) -> PyResult<Vec<u8>> {
+        let mut value = translate_error!(serde_json::to_value(data))?;
+        if let (Some(fingerprint), Some(fields)) = (key_fingerprint, value.as_object_mut()) {
+            fields.insert("key_fingerprint".to_string(), fingerprint.into());
+        }
+        let json = translate_error!(serde_json::to_vec(&value))?;
+        if !compress {
+            return Ok(json);
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        translate_error!(encoder.write_all(&json))?;
+        translate_error!(encoder.finish())
+    }
+
+    /// Deserialize the output of serialize, the compression is detected from the gzip magic bytes,
+    /// along with the key fingerprint if there is one
+    pub(crate) fn deserialize(bytes: &[u8]) -> PyResult<(concrete::VectorLWE, Option<u64>)> {
+        let value: serde_json::Value = if bytes.starts_with(&[0x1f, 0x8b]) {
+            translate_error!(serde_json::from_reader(GzDecoder::new(bytes)))?
+        } else {
+            translate_error!(serde_json::from_slice(bytes))?
+        };
+        let key_fingerprint = value.get("key_fingerprint").and_then(|f| f.as_u64());
+        let data = translate_error!(serde_json::from_value(value))?;
+        Ok((data, key_fingerprint))
+    }
+
+    /// Copy the n-th ciphertext of a structure into a single LWE
+    pub(crate) fn nth_as_lwe(data: &concrete::VectorLWE, n: usize) -> PyResult<concrete::LWE> {
+        if n >= data.nb_ciphertexts {
+            return Err(PyIndexError::new_err(format!(
+                "index {} out of range for {} ciphertexts", n, data.nb_ciphertexts)));
+        }
+        let size = data.dimension + 1;
+        let mut ct = translate_error!(concrete::LWE::zero(data.dimension))?;
+        ct.ciphertext.as_mut_tensor().as_mut_slice()
+            .copy_from_slice(&data.ciphertexts.as_tensor().as_slice()[n * size..(n + 1) * size]);
+        ct.variance = data.variances[n];
+        ct.encoder = data.encoders[n].clone();
+        Ok(ct)
+    }
+
+    /// Give `small` and `large` (with the larger delta) the same delta and padding, see align_encoders_with
+    pub(crate) fn align_pair(small: &mut concrete::LWE, large: &mut concrete::LWE) -> Result<(), String> {
+        let ratio = f64::log2(large.encoder.delta / small.encoder.delta);
+        let k = ratio.round();
+        if !helper_deltas_eq(small.encoder.delta * f64::powf(2., k), large.encoder.delta) {
+            return Err(format!(
+                "deltas {} and {} are not a power of 2 apart", small.encoder.delta, large.encoder.delta));
+        }
+        let k = k as usize;
+        if small.encoder.nb_bit_padding < k {
+            return Err(format!(
+                "{} bit(s) of padding are needed to widen delta {} to {}, got {}",
+                k, small.encoder.delta, large.encoder.delta, small.encoder.nb_bit_padding));
+        }
+        // the top k bits of padding become message bits: same ciphertext, interval 2^k times larger
+        small.encoder.nb_bit_padding -= k;
+        small.encoder.nb_bit_precision += k;
+        small.encoder.delta = large.encoder.delta;
+        let padding = usize::min(small.encoder.nb_bit_padding, large.encoder.nb_bit_padding);
+        for ct in [small, large] {
+            let extra = ct.encoder.nb_bit_padding - padding;
+            if extra > 0 {
+                ct.remove_padding_inplace(extra).map_err(|e| e.to_string())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Gather single LWE ciphertexts of the same dimension into a structure
+    pub(crate) fn from_lwes(cts: &[concrete::LWE]) -> PyResult<concrete::VectorLWE> {
+        let dimension = match cts.first() {
+            Some(ct) => ct.dimension,
+            None => return Err(PyValueError::new_err("no ciphertext to gather")),
+        };
+        let size = dimension + 1;
+        let mut data = translate_error!(concrete::VectorLWE::zero(dimension, cts.len()))?;
+        for (i, ct) in cts.iter().enumerate() {
+            if ct.dimension != dimension {
+                return Err(PyValueError::new_err(format!(
+                    "dimension mismatch: ciphertext {} has {}, expected {}", i, ct.dimension, dimension)));
+            }
+            data.ciphertexts.as_mut_tensor().as_mut_slice()[i * size..(i + 1) * size]
+                .copy_from_slice(ct.ciphertext.as_tensor().as_slice());
+            data.variances[i] = ct.variance;
+            data.encoders[i] = ct.encoder.clone();
+        }
+        Ok(data)
+    }
+
+    /// Check that the secret key has the dimension of the ciphertexts before encrypting into self
+    pub(crate) fn check_key_dimension(&self, sk: &crate::LWESecretKey) -> PyResult<()> {
+        if sk.data.dimension != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: the secret key has dimension {} but the ciphertexts have dimension {}",
+                sk.data.dimension, self.data.dimension)));
+        }
+        Ok(())
+    }
+
+
+    /// Check that multiplying each ciphertext by an integer cannot leave the interval of its
+    /// encoder, i.e. that both bounds of the interval times the integer stay in the interval
+    pub(crate) fn check_static_product_range(&self, messages: &[i32]) -> PyResult<()> {
+        let offending: Vec<usize> = messages.iter().zip(self.data.encoders.iter()).enumerate()
+            .filter(|(_, (m, enc))| {
+                let (min, max) = (enc.get_min(), enc.get_max());
+                let tolerance = enc.get_granularity() / 2.;
+                [min * **m as f64, max * **m as f64].iter()
+                    .any(|p| *p < min - tolerance || *p > max + tolerance)
+            })
+            .map(|(i, _)| i)
+            .collect();
+        if !offending.is_empty() {
+            return Err(PyValueError::new_err(format!(
+                "the products may leave the interval of the encoder at indexes {:?}", offending)));
+        }
+        Ok(())
+    }
+
+    /// Weighted sum of all the ciphertexts into a single one: the padding left after the
+    /// sum (ceil(log2(nb_ciphertexts)) bits) is spent on the multiplication
+    pub(crate) fn dot_with_padding(&self, weights: &[f64]) -> PyResult<concrete::VectorLWE> {
+        if weights.len() != self.data.nb_ciphertexts {
+            return Err(PyValueError::new_err(format!(
+                "expected {} weights, got {}", self.data.nb_ciphertexts, weights.len())));
+        }
+        let encoder = &self.data.encoders[0];
+        let nb_bit_sum = f64::log2(self.data.nb_ciphertexts as f64).ceil() as usize;
+        if encoder.nb_bit_padding <= nb_bit_sum {
+            return Err(PyValueError::new_err(format!(
+                "not enough padding: {} bits for a sum of {} ciphertexts",
+                encoder.nb_bit_padding, self.data.nb_ciphertexts)));
+        }
+        let max = weights.iter().fold(0., |m: f64, w| m.max(w.abs())).ceil().max(1.);
+        let nb_prec = encoder.nb_bit_precision.min(encoder.nb_bit_padding - nb_bit_sum);
+        let products = translate_error!(self.data.mul_constant_with_padding(weights, max, nb_prec))?;
+        translate_error!(products.sum_with_padding())
+    }
+
+    /// Check that `perm` contains each index of 0..nb_ciphertexts exactly once
+    pub(crate) fn check_permutation(&self, perm: &[usize]) -> PyResult<()> {
+        let nb = self.data.nb_ciphertexts;
+        if perm.len() != nb {
+            return Err(PyValueError::new_err(format!(
+                "expected a permutation of {} indexes, got {}", nb, perm.len())));
+        }
+        let mut seen = vec![false; nb];
+        for p in perm.iter() {
+            if *p >= nb || seen[*p] {
+                return Err(PyValueError::new_err(format!(
+                    "{:?} is not a permutation of 0..{}", perm, nb)));
+            }
+            seen[*p] = true;
+        }
+        Ok(())
+    }
+
+    /// Check that the ciphertexts can go through max_with_bootstrap: same encoders with at least
+    /// 2 bits of padding and a bsk whose output dimension equals its input dimension
+    pub(crate) fn check_max_preconditions(&self, bsk: &crate::LWEBSK) -> PyResult<()> {
+        let encoder = &self.data.encoders[0];
+        if self.data.encoders.iter().any(|e| e != encoder) {
+            return Err(PyValueError::new_err("all the ciphertexts must share the same encoder"));
+        }
+        if encoder.nb_bit_padding < 2 {
+            return Err(PyValueError::new_err(format!(
+                "at least 2 bits of padding are required, got {}", encoder.nb_bit_padding)));
+        }
+        let dimension_out = bsk.data.dimension * bsk.data.polynomial_size;
+        if bsk.data.get_lwe_dimension() != self.data.dimension || dimension_out != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "the bsk must map dimension {} to itself, got {} -> {}",
+                self.data.dimension, bsk.data.get_lwe_dimension(), dimension_out)));
+        }
+        Ok(())
+    }
+
+    /// Bootstrap all the ciphertexts with the function f in parallel, building one lookup table per
+    /// distinct input encoder while holding the GIL (f may call Python), the first error of f is returned
+    fn map_with<F: Fn(f64) -> PyResult<f64>>(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        f: F,
+        encoder_output: &concrete::Encoder,
+        batch: Batch,
+    ) -> PyResult<crate::VectorLWE> {
+        let nb = self.data.nb_ciphertexts;
+        // one table per distinct input encoder
+        let mut encoders: Vec<&concrete::Encoder> = Vec::new();
+        let mut tables: Vec<Vec<Torus>> = Vec::new();
+        let mut table_of = Vec::with_capacity(nb);
+        for encoder in self.data.encoders.iter() {
+            let t = match encoders.iter().position(|e| *e == encoder) {
+                Some(t) => t,
+                None => {
+                    let error = std::cell::RefCell::new(None);
+                    let table = bsk.data.generate_functional_look_up_table(
+                        encoder, encoder_output, |x| match f(x) {
+                            Ok(y) => y,
+                            Err(e) => {
+                                error.borrow_mut().get_or_insert(e);
+                                0.
+                            }
+                        });
+                    if let Some(e) = error.into_inner() {
+                        return Err(e);
+                    }
+                    encoders.push(encoder);
+                    tables.push(translate_error!(table)?);
+                    tables.len() - 1
+                }
+            };
+            table_of.push(t);
+        }
+        self.bootstrap_with_tables(py, bsk, &tables, &table_of, &vec![encoder_output.clone(); nb], batch)
+    }
+
+    /// Bootstrap the i-th ciphertext with tables[table_of[i]] into encoders_output[i], in parallel
+    /// with the GIL released
+    fn bootstrap_with_tables(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        tables: &[Vec<Torus>],
+        table_of: &[usize],
+        encoders_output: &[concrete::Encoder],
+        batch: Batch,
+    ) -> PyResult<crate::VectorLWE> {
+        let data = &self.data;
+        let data = VectorLWE::parallel_bootstrap(py, bsk, data.nb_ciphertexts, batch, |key, i| {
+            let ct = VectorLWE::nth_as_lwe(data, i).map_err(|e| e.to_string())?;
+            crate::LWEBSK::bootstrap_with_table(key, &ct, &tables[table_of[i]], &encoders_output[i])
+        })?;
+        Ok(VectorLWE{ data, key_fingerprint: None, shape: None })
+    }
+
+    /// Bootstrap every ciphertext through `f` in parallel with the GIL released (see Batch);
+    /// the output encoder defaults to the encoder of each input ciphertext
+    pub(crate) fn bootstrap_all_with_function<F: Fn(f64) -> f64 + Sync>(
+        &self,
+        py: Python,
+        bsk: &crate::LWEBSK,
+        f: F,
+        encoder_output: Option<&concrete::Encoder>,
+        batch: Batch,
+    ) -> PyResult<concrete::VectorLWE> {
+        let data = &self.data;
+        let f = &f;
+        VectorLWE::parallel_bootstrap(py, bsk, data.nb_ciphertexts, batch, |key, i| {
+            let ct = VectorLWE::nth_as_lwe(data, i).map_err(|e| e.to_string())?;
+            let encoder = encoder_output.unwrap_or(&data.encoders[i]);
+            ct.bootstrap_with_function(key, f, encoder).map_err(|e| e.to_string())
+        })
+    }
+
+    /// Compute bootstrap(key, i) for i in 0..nb on a pool of threads with the GIL released, each
+    /// thread holding its own copy of the bootstrapping key (dropped on return), and gather the
+    /// outputs in order
+    /// The indices are processed batch.chunk_size at a time and the outputs of a chunk are copied
+    /// into the result before the next one starts, so only one chunk of outputs is alive at once
+    pub(crate) fn parallel_bootstrap<B>(
+        py: Python,
+        bsk: &crate::LWEBSK,
+        nb: usize,
+        batch: Batch,
+        bootstrap: B,
+    ) -> PyResult<concrete::VectorLWE>
+    where
+        B: Fn(&concrete::LWEBSK, usize) -> Result<concrete::LWE, String> + Sync,
+    {
+        if batch.chunk_size == Some(0) {
+            return Err(PyValueError::new_err("the chunk size must be at least 1"));
+        }
+        let chunk_size = batch.chunk_size.unwrap_or(nb).clamp(1, nb.max(1));
+        let threads = batch.max_threads.unwrap_or_else(rayon::current_num_threads).clamp(1, chunk_size);
+        let pool = translate_error!(rayon::ThreadPoolBuilder::new().num_threads(threads).build())?;
+        // the fft buffers of a bsk cannot be shared between threads
+        let mut keys: Vec<concrete::LWEBSK> = (0..threads).map(|_| bsk.data.clone()).collect();
+        let dimension = bsk.data.dimension * bsk.data.polynomial_size;
+        let size = dimension + 1;
+        let mut res = translate_error!(concrete::VectorLWE::zero(dimension, nb))?;
+        let bootstrap = &bootstrap;
+        for start in (0..nb).step_by(chunk_size) {
+            let end = nb.min(start + chunk_size);
+            let per_thread = (end - start).div_ceil(threads);
+            let keys = &mut keys;
+            let results = py.allow_threads(|| pool.install(|| {
+                keys.par_iter_mut().enumerate().map(|(t, key)| {
+                    (start + t * per_thread..end.min(start + (t + 1) * per_thread))
+                        .map(|i| bootstrap(key, i))
+                        .collect::<Result<Vec<_>, String>>()
+                }).collect::<Result<Vec<_>, String>>()
+            }));
+            for (i, ct) in translate_error!(results)?.into_iter().flatten().enumerate() {
+                let n = start + i;
+                res.ciphertexts.as_mut_tensor().as_mut_slice()[n * size..(n + 1) * size]
+                    .copy_from_slice(ct.ciphertext.as_tensor().as_slice());
+                res.variances[n] = ct.variance;
+                res.encoders[n] = ct.encoder;
+            }
+        }
+        Ok(res)
+    }
+
+    /// Number of bits of precision of each ciphertext that are not overwritten by its noise
+    pub(crate) fn noise_budgets(&self) -> Vec<usize> {
+        let torus_bit = <Torus>::BITS as usize;
+        self.data.variances.iter().zip(self.data.encoders.iter()).map(|(variance, encoder)| {
+            let noise = npe::nb_bit_from_variance_99(*variance, torus_bit).min(torus_bit);
+            let free = torus_bit.saturating_sub(encoder.nb_bit_padding + noise);
+            encoder.nb_bit_precision.min(free)
+        }).collect()
+    }
+
+    /// Subtract b from a with sub_with_padding, keeping the granularity of the inputs
+    /// sub_with_padding doubles the interval but keeps the number of bits of precision, so a
+    /// bootstrap of the difference would round it to twice the input granularity; the difference
+    /// of two messages of the same encoder lies on the same grid and needs exactly one more bit
+    pub(crate) fn sub_keeping_granularity(
+        a: &concrete::VectorLWE,
+        b: &concrete::VectorLWE,
+    ) -> PyResult<concrete::VectorLWE> {
+        let mut diff = translate_error!(a.sub_with_padding(b))?;
+        for encoder in diff.encoders.iter_mut() {
+            encoder.nb_bit_precision += 1;
+        }
+        Ok(diff)
+    }
+
+    /// Homomorphic max of two single ciphertexts sharing the encoder of self:
+    /// max(a, b) = b + relu(a - b), where the relu is computed with a bootstrap
+    pub(crate) fn max_with_bootstrap(
+        &self,
+        a: &concrete::VectorLWE,
+        b: &concrete::VectorLWE,
+        bsk: &crate::LWEBSK,
+    ) -> PyResult<concrete::VectorLWE> {
+        let encoder = &self.data.encoders[0];
+        let mut encoder_relu = encoder.clone();
+        encoder_relu.o = 0.;
+        let diff = Self::sub_keeping_granularity(a, b)?;
+        let relu = translate_error!(diff.bootstrap_nth_with_function(
+            &bsk.data, |x| x.max(0.), &encoder_relu, 0))?;
+        translate_error!(b.add_with_new_min(&relu, &[encoder.o]))
+    }
+}
+
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<VectorLWE>()?;
 
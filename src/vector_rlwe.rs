@@ -5,6 +5,10 @@ use pyo3::exceptions::*;
 // use pyo3::types::{PyList, PyFunction};
 use concrete;
 use concrete::{Torus};
+use concrete_core::crypto::encoding::PlaintextList;
+use concrete_core::math::tensor::{AsMutSlice, AsMutTensor, AsRefTensor};
+use concrete_npe as npe;
+use colored::Colorize;
 use super::translate_error;
 
 /// Structure containing a list of RLWE ciphertexts
@@ -20,7 +24,7 @@ use super::translate_error;
 /// * `polynomial_size` - the number of coefficients in a polynomial
 /// * `nb_ciphertexts` - the number of RLWE ciphertexts present in the list
 /// * `encoders` - the encoders of each RLWE ciphertext of the list
-#[pyclass]
+#[pyclass(module = "pyconcrete")]
 #[derive(Debug, Clone, PartialEq)]
 pub struct VectorRLWE {
     // pub ciphertexts: GlweList<Vec<Torus>>,
@@ -50,9 +54,12 @@ impl VectorRLWE {
         self.data.dimension
     }
 
+    /// Raise a DimensionError if the new dimension does not match the size of the buffer
     #[setter]
-    pub fn set_dimension(&mut self, v: usize) {
+    pub fn set_dimension(&mut self, v: usize) -> PyResult<()> {
+        self.check_layout(v, self.data.polynomial_size, self.data.nb_ciphertexts)?;
         self.data.dimension = v;
+        Ok(())
     }
 
     #[getter]
@@ -60,9 +67,17 @@ impl VectorRLWE {
         self.data.polynomial_size
     }
 
+    /// Raise a NotPowerOfTwoError if the new size is not a power of 2
+    /// and a DimensionError if it does not match the size of the buffer
     #[setter]
-    pub fn set_polynomial_size(&mut self, v: usize) {
+    pub fn set_polynomial_size(&mut self, v: usize) -> PyResult<()> {
+        if !v.is_power_of_two() {
+            return Err(PyValueError::new_err(format!(
+                "NotPowerOfTwoError: polynomial_size (= {}) must be a power of 2", v)));
+        }
+        self.check_layout(self.data.dimension, v, self.data.nb_ciphertexts)?;
         self.data.polynomial_size = v;
+        Ok(())
     }
 
     #[getter]
@@ -70,9 +85,12 @@ impl VectorRLWE {
         self.data.nb_ciphertexts
     }
 
+    /// Raise a DimensionError if the new number does not match the size of the buffer
     #[setter]
-    pub fn set_nb_ciphertexts(&mut self, v: usize) {
+    pub fn set_nb_ciphertexts(&mut self, v: usize) -> PyResult<()> {
+        self.check_layout(self.data.dimension, self.data.polynomial_size, v)?;
         self.data.nb_ciphertexts = v;
+        Ok(())
     }
 
     #[getter]
@@ -85,6 +103,40 @@ impl VectorRLWE {
     //     self.data.encoders = v;
     // }
 
+    /// Get the encoder of one slot (i.e. one coefficient) of one RLWE ciphertext
+    ///
+    /// # Arguments
+    /// * `ciphertext_index` - the index of the RLWE ciphertext
+    /// * `slot_index` - the index of the coefficient in the polynomial
+    ///
+    /// # Output
+    /// * a copy of the encoder
+    /// * IndexError if one of the indexes is out of bounds
+    pub fn get_encoder_at(&self, ciphertext_index: usize, slot_index: usize) -> PyResult<crate::Encoder> {
+        let index = self.slot_index(ciphertext_index, slot_index)?;
+        Ok(crate::Encoder{ data: self.data.encoders[index].clone() })
+    }
+
+    /// Set the encoder of one slot (i.e. one coefficient) of one RLWE ciphertext
+    ///
+    /// # Arguments
+    /// * `ciphertext_index` - the index of the RLWE ciphertext
+    /// * `slot_index` - the index of the coefficient in the polynomial
+    /// * `encoder` - the new encoder of the slot
+    ///
+    /// # Output
+    /// * IndexError if one of the indexes is out of bounds
+    pub fn set_encoder_at(
+        &mut self,
+        ciphertext_index: usize,
+        slot_index: usize,
+        encoder: &crate::Encoder,
+    ) -> PyResult<()> {
+        let index = self.slot_index(ciphertext_index, slot_index)?;
+        self.data.encoders[index].copy(&encoder.data);
+        Ok(())
+    }
+
     /// Instantiate a new VectorRLWE filled with zeros from a polynomial size, a dimension and a number of ciphertexts
     ///
     /// # Arguments
@@ -452,7 +504,7 @@ impl VectorRLWE {
         n_ciphertext: usize,
     ) -> PyResult<crate::VectorLWE> {
         let data = translate_error!(self.data.extract_1_lwe(n_coeff, n_ciphertext))?;
-        Ok(crate::VectorLWE{ data })
+        Ok(crate::VectorLWE{ data, key_fingerprint: None, shape: None })
     }
 
     /// Add small messages to a VectorRLWE ciphertext and does not change the encoding but changes the bodies of the ciphertexts
@@ -688,15 +740,153 @@ impl VectorRLWE {
         translate_error!(self.data.mul_constant_with_padding_inplace(&constants, max_constant, nb_bit_padding))
     }
 
+    /// Compute the opposite of every RLWE ciphertext, the encoders of the valid slots are updated
+    /// and the invalid slots are left as they are
+    ///
+    /// # Output
+    /// * a new VectorRLWE
+    /// * InvalidEncoderError - if there is no valid encoder
+    pub fn opposite(&self) -> PyResult<crate::VectorRLWE> {
+        let mut res = self.clone();
+        res.opposite_inplace()?;
+        Ok(res)
+    }
+
+    /// Compute the opposite of every RLWE ciphertext, the encoders of the valid slots are updated
+    /// and the invalid slots are left as they are
+    ///
+    /// # Output
+    /// * InvalidEncoderError - if there is no valid encoder
+    pub fn opposite_inplace(&mut self) -> PyResult<()> {
+        if self.data.nb_valid() == 0 {
+            return Err(PyValueError::new_err("there is no valid encoder in the VectorRLWE"));
+        }
+        let polynomial_size = self.data.polynomial_size;
+        let body_offset = polynomial_size * self.data.dimension;
+        let ct_size = polynomial_size * (self.data.dimension + 1);
+        for (ct, encoders) in self.data.ciphertexts.as_mut_tensor().as_mut_slice()
+            .chunks_mut(ct_size)
+            .zip(self.data.encoders.chunks_mut(polynomial_size))
+        {
+            ct.iter_mut().for_each(|a| *a = a.wrapping_neg());
+            for (coeff, encoder) in ct[body_offset..].iter_mut().zip(encoders.iter_mut()) {
+                if !encoder.is_valid() {
+                    continue;
+                }
+                // same correction as the opposite of an LWE: the interval is mirrored
+                let shift = <Torus>::BITS as usize - encoder.nb_bit_padding - encoder.nb_bit_precision;
+                let mut cor: Torus = 0;
+                if encoder.nb_bit_padding > 0 {
+                    cor = 1 << (<Torus>::BITS as usize - encoder.nb_bit_padding);
+                }
+                *coeff = coeff.wrapping_add(cor.wrapping_sub(1 << shift));
+                translate_error!(encoder.opposite_inplace())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Pack LWE ciphertexts into the first slots of a single RLWE ciphertext with a packing key
+    /// switching key, the i-th ciphertext of `lwe` going to the i-th coefficient
+    ///
+    /// # Argument
+    /// * `lwe` - the LWE ciphertexts to pack
+    /// * `packing_ksk` - a packing key switching key from the key of `lwe` to an RLWE key
+    ///
+    /// # Output
+    /// * a VectorRLWE with one ciphertext, the slots after the packed ones have invalid encoders
+    /// * DimensionError - if the dimension of `lwe` does not match the key
+    /// * ValueError - if there are more ciphertexts than coefficients in a polynomial
+    #[staticmethod]
+    pub fn from_vector_lwe(
+        lwe: &crate::VectorLWE,
+        packing_ksk: &crate::RLWEKSK,
+    ) -> PyResult<crate::VectorRLWE> {
+        let data = translate_error!(packing_ksk.pack(&lwe.data))?;
+        Ok(VectorRLWE{ data })
+    }
+
     /// Return the number of valid encoders (i.e. how many messages are carried in those RLWE ciphertexts)
     pub fn nb_valid(&self) -> usize {
         self.data.nb_valid()
     }
 
+    /// Return which encoders are valid, i.e. which coefficients carry a message
+    ///
+    /// # Output
+    /// * a list with, for each RLWE ciphertext, one boolean per coefficient of its polynomial
+    pub fn valid_mask(&self) -> Vec<Vec<bool>> {
+        self.data.encoders
+            .chunks(self.data.polynomial_size)
+            .map(|encoders| encoders.iter().map(|e| e.is_valid()).collect())
+            .collect()
+    }
+
     pub fn get_ciphertext_size(&self) -> usize {
         self.data.polynomial_size * (self.data.dimension + 1)
     }
 
+    /// Return the size in bytes of one RLWE ciphertext with the parameters of self
+    pub fn ciphertext_size_bytes(&self) -> usize {
+        self.get_ciphertext_size() * std::mem::size_of::<Torus>()
+    }
+
+    /// Return the size in bytes of all the RLWE ciphertexts of self (masks and bodies only)
+    pub fn total_size_bytes(&self) -> usize {
+        self.ciphertext_size_bytes() * self.data.nb_ciphertexts
+    }
+
+    /// Pretty print the bits of each valid slot: padding in blue, message in green and noise in red
+    pub fn pp(&self) {
+        for (variance, encoder) in self.data.variances.iter().zip(self.data.encoders.iter()) {
+            if !encoder.is_valid() {
+                continue;
+            }
+            let torus_bit = <Torus>::BITS as usize;
+            let noise = npe::nb_bit_from_variance_99(*variance, torus_bit).min(torus_bit);
+            let nothing = torus_bit.saturating_sub(
+                encoder.nb_bit_padding + encoder.nb_bit_precision + noise);
+            println!(
+                "{}{}{}{}",
+                "o".repeat(encoder.nb_bit_padding).blue().bold(),
+                "o".repeat(encoder.nb_bit_precision).green().bold(),
+                "o".repeat(nothing).bold(),
+                "o".repeat(noise).red().bold()
+            );
+        }
+    }
+
+    /// Decrypt all the coefficients of all the RLWE ciphertexts into a NumPy array of shape
+    /// (nb_ciphertexts, polynomial_size); coefficients without a valid encoder are NaN
+    ///
+    /// # Argument
+    /// * `sk` - an RLWE secret key
+    ///
+    /// # Output
+    /// * a new NumPy array
+    /// * DimensionError - if the ciphertext and the key have incompatible dimensions
+    /// * PolynomialSizeError - if the ciphertext and the key have incompatible polynomial sizes
+    /// * ImportError if NumPy is not installed
+    pub fn coefficients_numpy(&self, py: Python, sk: &crate::RLWESecretKey) -> PyResult<PyObject> {
+        let raw = self.decrypt_all_raw(sk)?;
+        let mut rows = Vec::with_capacity(self.data.nb_ciphertexts);
+        for (pts, encoders) in raw.chunks(self.data.polynomial_size)
+            .zip(self.data.encoders.chunks(self.data.polynomial_size))
+        {
+            let row = pts.iter().zip(encoders.iter())
+                .map(|(pt, enc)| if enc.is_valid() {
+                    translate_error!(enc.decode_single(*pt))
+                } else {
+                    Ok(f64::NAN)
+                })
+                .collect::<PyResult<Vec<f64>>>()?;
+            rows.push(row);
+        }
+        let numpy = py.import("numpy")?;
+        let array = numpy.call_method1("array", (rows,))?;
+        Ok(array.into())
+    }
+
     pub fn save(&self, path: &str) -> PyResult<()> {
         translate_error!(self.data.save(path))
     }
@@ -707,11 +897,79 @@ impl VectorRLWE {
         Ok(VectorRLWE{ data })
     }
 
+    /// Return false: a VectorRLWE never holds secret key material, only the public ciphertexts,
+    /// their variances and encoders, so its pickle and its saved files are safe to share.
+    /// This is an invariant of the type, any field added to it must keep it
+    pub fn contains_secret(&self) -> bool {
+        false
+    }
+
+    /// Support for pickle: the object is rebuilt from its serialization by _from_bytes
+    pub fn __reduce__(&self, py: Python) -> PyResult<(PyObject, (PyObject,))> {
+        crate::helper_reduce::<VectorRLWE, _>(py, &self.data)
+    }
+
+    /// Rebuild the object from the serialization produced by __reduce__
+    #[staticmethod]
+    pub fn _from_bytes(bytes: &[u8]) -> PyResult<VectorRLWE> {
+        let data = crate::helper_from_bytes(bytes)?;
+        Ok(VectorRLWE{ data })
+    }
+
     pub fn __repr__(&self) -> String {
         self.data.to_string()
     }
 }
 
+impl VectorRLWE {
+    /// Check that the ciphertexts buffer holds exactly nb_ciphertexts RLWE ciphertexts
+    /// with the given dimension and polynomial size
+    fn check_layout(&self, dimension: usize, polynomial_size: usize, nb_ciphertexts: usize) -> PyResult<()> {
+        let expected = nb_ciphertexts * (dimension + 1) * polynomial_size;
+        let len = self.data.ciphertexts.as_tensor().len();
+        if expected != len {
+            return Err(PyValueError::new_err(format!(
+                "DimensionError: {} ciphertexts of dimension {} and polynomial size {} need {} coefficients but the buffer has {}",
+                nb_ciphertexts, dimension, polynomial_size, expected, len)));
+        }
+        Ok(())
+    }
+
+    /// Compute the phase of every coefficient of every ciphertext
+    pub(crate) fn decrypt_all_raw(&self, sk: &crate::RLWESecretKey) -> PyResult<Vec<Torus>> {
+        if sk.data.polynomial_size != self.data.polynomial_size {
+            return Err(PyValueError::new_err(format!(
+                "polynomial size mismatch: key {}, ciphertext {}",
+                sk.data.polynomial_size, self.data.polynomial_size)));
+        }
+        if sk.data.dimension != self.data.dimension {
+            return Err(PyValueError::new_err(format!(
+                "dimension mismatch: key {}, ciphertext {}", sk.data.dimension, self.data.dimension)));
+        }
+        let mut raw: Vec<Torus> = vec![0; self.data.polynomial_size * self.data.nb_ciphertexts];
+        sk.data.val.decrypt_glwe_list(
+            &mut PlaintextList::from_container(raw.as_mut_slice()),
+            &self.data.ciphertexts,
+        );
+        Ok(raw)
+    }
+
+    /// Compute the position in `encoders` and `variances` of a coefficient of a ciphertext
+    pub(crate) fn slot_index(&self, ciphertext_index: usize, slot_index: usize) -> PyResult<usize> {
+        if ciphertext_index >= self.data.nb_ciphertexts {
+            return Err(PyIndexError::new_err(format!(
+                "ciphertext index {} out of range for {} ciphertexts",
+                ciphertext_index, self.data.nb_ciphertexts)));
+        }
+        if slot_index >= self.data.polynomial_size {
+            return Err(PyIndexError::new_err(format!(
+                "slot index {} out of range for a polynomial size of {}",
+                slot_index, self.data.polynomial_size)));
+        }
+        Ok(ciphertext_index * self.data.polynomial_size + slot_index)
+    }
+}
+
 pub fn register(_py: Python, m: &PyModule) -> PyResult<()> {
     m.add_class::<VectorRLWE>()?;
 
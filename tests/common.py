@@ -0,0 +1,56 @@
+"""Shared fixtures for the pyconcrete test suite.
+
+The keys below use small, insecure parameters so that the whole suite runs in
+seconds; they are generated once per process and must never be used outside of
+the tests.
+"""
+import functools
+import math
+import unittest
+
+import pyconcrete as pc
+
+# an encoder with a granularity of exactly 1 over [-8, 7]
+INT_ENCODER = (-8., 7., 4, 1)
+
+
+@functools.lru_cache(maxsize=None)
+def keys():
+    """Return `(sk, rsk, bsk, sk_out, ksk)`.
+
+    `bsk` bootstraps ciphertexts under `sk` into ciphertexts under `sk_out`,
+    `ksk` switches them back from `sk_out` to `sk`.
+    """
+    sk = pc.LWESecretKey(pc.LWEParams(256, -20))
+    rsk = pc.RLWESecretKey(pc.RLWEParams(512, 1, -30))
+    bsk = pc.LWEBSK(sk, rsk, 6, 4)
+    sk_out = rsk.to_lwe_secret_key()
+    ksk = pc.LWEKSK(sk_out, sk, 2, 8)
+    return sk, rsk, bsk, sk_out, ksk
+
+
+def int_encoder(nb_bit_padding=1):
+    return pc.Encoder(INT_ENCODER[0], INT_ENCODER[1], INT_ENCODER[2], nb_bit_padding)
+
+
+class TestCase(unittest.TestCase):
+
+    def setUp(self):
+        self.sk, self.rsk, self.bsk, self.sk_out, self.ksk = keys()
+
+    def assertAllClose(self, actual, expected, tol):
+        actual, expected = list(actual), list(expected)
+        self.assertEqual(len(actual), len(expected))
+        for i, (a, e) in enumerate(zip(actual, expected)):
+            self.assertTrue(math.isfinite(a) and abs(a - e) <= tol,
+                            "index {}: {} is not within {} of {}".format(i, a, tol, e))
+
+
+@functools.lru_cache(maxsize=None)
+def self_keys():
+    """Return `(sk, bsk)` where `bsk` bootstraps ciphertexts under `sk` back under `sk`.
+
+    Needed by the operations that combine bootstrapped values with their inputs.
+    """
+    _, rsk, _, sk_out, _ = keys()
+    return sk_out, pc.LWEBSK(sk_out, rsk, 6, 4)
@@ -0,0 +1,84 @@
+import os
+import pickle
+import struct
+import tempfile
+
+import pyconcrete as pc
+
+from common import TestCase, int_encoder
+
+
+class TestLWEBSK(TestCase):
+
+    def assertBootstrapsIdentically(self, bsk, other):
+        for m in (-5., 0., 3.):
+            c = pc.LWE.encode_encrypt(self.sk, m, int_encoder())
+            self.assertEqual(c.bootstrap(bsk).decrypt_decode(self.sk_out),
+                             c.bootstrap(other).decrypt_decode(self.sk_out))
+
+    def test_raw_round_trip_bootstraps_identically(self):
+        bsk = self.bsk
+        other = pc.LWEBSK.from_raw(bsk.dimension, bsk.polynomial_size, bsk.base_log, bsk.level,
+                                   bsk.variance, bsk.to_raw())
+        self.assertIsInstance(bsk.to_raw(), bytes)
+        self.assertEqual(other.get_lwe_dimension(), bsk.get_lwe_dimension())
+        self.assertBootstrapsIdentically(bsk, other)
+
+    def test_from_raw_rejects_overflowing_parameters(self):
+        with self.assertRaises(ValueError):
+            pc.LWEBSK.from_raw(2 ** 62, 2 ** 10, 6, 4, 0., bytes(16))
+
+    def test_is_compatible_with(self):
+        e = int_encoder()
+        compatible = pc.LWE.encode_encrypt(self.sk, 1., e)
+        incompatible = pc.LWE.encode_encrypt(self.sk_out, 1., e)
+        self.assertTrue(self.bsk.is_compatible_with(compatible))
+        self.assertFalse(self.bsk.is_compatible_with(incompatible))
+        self.assertTrue(self.bsk.is_compatible_with(pc.VectorLWE.encode_encrypt(self.sk, [1.], e)))
+        self.assertFalse(self.bsk.is_compatible_with(
+            pc.VectorLWE.encode_encrypt(self.sk_out, [1.], e)))
+
+    def test_repr_is_short_and_shows_the_parameters(self):
+        r = repr(self.bsk)
+        self.assertLess(len(r), 200)
+        self.assertIn('base_log', r)
+        self.assertIn('level', r)
+        self.assertGreater(len(self.bsk.describe()), len(r))
+
+    def test_unpickled_key_bootstraps_identically(self):
+        other = pickle.loads(pickle.dumps(self.bsk))
+        self.assertIsInstance(other, pc.LWEBSK)
+        self.assertBootstrapsIdentically(self.bsk, other)
+
+    def test_mmap_load_bootstraps_identically(self):
+        with tempfile.TemporaryDirectory() as d:
+            path = os.path.join(d, 'bsk')
+            self.bsk.save(path)
+            self.assertBootstrapsIdentically(pc.LWEBSK.load(path), pc.LWEBSK.load_mmap(path))
+            # a header whose sizes overflow cannot match the file
+            words = [8, 0, 2 ** 62, 1024, 6, 4, 256, 0, 0]
+            with open(path, 'wb') as f:
+                f.write(struct.pack('<9Q', *words))
+            with self.assertRaisesRegex(ValueError, 'does not match'):
+                pc.LWEBSK.load_mmap(path)
+
+    def test_repeated_identity_bootstraps_use_the_lut_cache(self):
+        self.bsk.clear_lut_cache()
+        self.assertEqual(self.bsk.lut_cache_info(), (0, 0))
+        c = pc.LWE.encode_encrypt(self.sk, 3., int_encoder())
+        first = pickle.dumps(c.bootstrap(self.bsk))
+        self.assertEqual(self.bsk.lut_cache_info(), (1, 0))
+        for k in range(1, 4):
+            self.assertEqual(pickle.dumps(c.bootstrap(self.bsk)), first)
+            self.assertEqual(self.bsk.lut_cache_info(), (1, k))
+        self.bsk.clear_lut_cache()
+        self.assertEqual(self.bsk.lut_cache_info(), (0, 0))
+
+    def test_check_params(self):
+        lwe, rlwe = pc.LWEParams(256, -20), pc.RLWEParams(512, 1, -30)
+        # the parameters of the test keys
+        pc.LWEBSK.check_params(lwe, rlwe, 6, 4)
+        with self.assertRaisesRegex(ValueError, 'base_log \\* level = 80'):
+            pc.LWEBSK.check_params(lwe, rlwe, 20, 4)
+        with self.assertRaisesRegex(ValueError, 'rounding drift'):
+            pc.LWEBSK.check_params(pc.LWEParams(1024, -30), pc.RLWEParams(16, 1, -30), 6, 4)
@@ -0,0 +1,103 @@
+import random
+
+import pyconcrete as pc
+
+from common import TestCase, int_encoder, self_keys
+
+
+class TestEncoder(TestCase):
+
+    def test_bits_used_by_grows_with_the_message(self):
+        e = int_encoder()
+        self.assertLess(e.bits_used_by(-7.), e.bits_used_by(6.))
+        self.assertLessEqual(e.bits_used_by(6.), e.nb_bit_precision)
+
+    def test_log_encoding_round_trip(self):
+        e = pc.Encoder.new_log_scale(1., 100., 8, 1)
+        messages = [1.5, 2., 10., 50., 99.]
+        p = e.encode_log(messages)
+        decoded = [e.decode_log(x) for x in p.plaintexts]
+        for m, d in zip(messages, decoded):
+            # the precision is relative on a log scale
+            self.assertAlmostEqual(d / m, 1., delta=0.05)
+        with self.assertRaises(ValueError):
+            pc.Encoder.new_log_scale(0., 100., 8, 1)
+
+    def test_encrypt_matches_vector_encode_encrypt(self):
+        e = int_encoder()
+        messages = [1., -2., 5.]
+        v = e.encrypt(self.sk, messages)
+        w = pc.VectorLWE.encode_encrypt(self.sk, messages, e)
+        self.assertEqual((v.nb_ciphertexts, v.dimension), (w.nb_ciphertexts, w.dimension))
+        self.assertEqual([x.get_min() for x in v.encoders], [x.get_min() for x in w.encoders])
+        self.assertAllClose(v.decrypt_decode(self.sk), w.decrypt_decode(self.sk), 0.5)
+        with self.assertRaises(ValueError):
+            e.encrypt(self.sk, [100.])
+
+    def test_snap_matches_a_rounding_encode_decode(self):
+        rng = random.Random(1377)
+        for args in ((-8., 7., 4, 1), (0., 1., 3, 0), (-1., 2.5, 5, 2)):
+            e = pc.Encoder(*args)
+            rounding = pc.Encoder.new_rounding_context(*args)
+            self.assertEqual(e.num_levels(), 2 ** args[2])
+            for _ in range(50):
+                m = rng.uniform(e.get_min(), e.get_max())
+                self.assertAlmostEqual(e.snap(m), rounding.encode_single(m).decode()[0])
+
+    def test_with_granularity_picks_the_smallest_precision(self):
+        e = pc.Encoder(-1., 1., 3, 0)
+        for granularity in (0.5, 0.1, 0.01):
+            with self.subTest(granularity=granularity):
+                g = e.with_granularity(granularity)
+                self.assertLessEqual(g.get_granularity(), granularity)
+                self.assertGreater(g.get_granularity() * 2, granularity)
+                self.assertEqual((g.get_min(), g.delta), (e.get_min(), e.delta))
+        with self.assertRaises(ValueError):
+            e.with_granularity(1e-30)
+
+    def test_for_product_decodes_the_product_of_two_ciphertexts(self):
+        sk, bsk = self_keys()
+        e = pc.Encoder(0., 3., 4, 4)
+        out = e.for_product(e, 1)
+        self.assertLessEqual(out.get_min(), 0.)
+        self.assertGreaterEqual(out.get_max(), 9.)
+        for x, y in ((2., 3.), (2., 2.), (1., 2.)):
+            with self.subTest(x=x, y=y):
+                a = pc.LWE.encode_encrypt(sk, x, e)
+                b = pc.LWE.encode_encrypt(sk, y, e)
+                # mul_from_bootstrap picks its own encoder, move the product into out
+                p = a.mul_from_bootstrap(b, bsk).bootstrap_with_function(bsk, lambda t: t, out)
+                self.assertAlmostEqual(p.decrypt_decode(sk), x * y, delta=out.get_granularity())
+
+    def test_from_tuple_inverts_as_tuple(self):
+        for e in (int_encoder(), pc.Encoder(-1.6, 2.9, 4, 1), pc.Encoder.new_rounding_context(0., 7., 3, 2)):
+            with self.subTest(encoder=e.as_tuple()):
+                other = pc.Encoder.from_tuple(e.as_tuple())
+                self.assertEqual(other.as_tuple(), e.as_tuple())
+                self.assertEqual((other.get_min(), other.get_granularity()), (e.get_min(), e.get_granularity()))
+
+    def test_move_padding_to_precision_halves_the_granularity_per_bit(self):
+        e = pc.Encoder(-8., 8., 4, 3)
+        for nb in range(4):
+            with self.subTest(nb=nb):
+                m = e.move_padding_to_precision(nb)
+                self.assertEqual((m.nb_bit_precision, m.nb_bit_padding), (4 + nb, 3 - nb))
+                self.assertAlmostEqual(m.get_granularity(), e.get_granularity() / 2 ** nb)
+                self.assertEqual(m.get_min(), e.get_min())
+        with self.assertRaises(ValueError):
+            e.move_padding_to_precision(4)
+
+    def test_num_levels_of_a_64_bit_precision(self):
+        e = pc.Encoder(0., 1., 64, 0)
+        with self.assertRaises(ValueError):
+            e.num_levels()
+        # snap does not need the number of levels to fit in an integer
+        self.assertAlmostEqual(e.snap(0.3), 0.3)
+
+    def test_from_tuple_rejects_invalid_encoders(self):
+        for t in [(0., 0., 4, 1, False), (0., -1., 4, 1, False), (float('nan'), 1., 4, 1, False),
+                  (0., float('inf'), 4, 1, False), (0., 1., 60, 5, False)]:
+            with self.subTest(t=t):
+                with self.assertRaises(ValueError):
+                    pc.Encoder.from_tuple(t)
+        self.assertEqual(pc.Encoder.from_tuple((0., 1., 60, 4, False)).as_tuple(), (0., 1., 60, 4, False))
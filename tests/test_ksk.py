@@ -0,0 +1,28 @@
+import pyconcrete as pc
+
+from common import TestCase, int_encoder
+
+
+class TestLWEKSK(TestCase):
+
+    def test_for_bootstrap_keyswitch_then_bootstrap(self):
+        ksk = pc.LWEKSK.for_bootstrap(self.sk_out, self.sk, self.bsk, 2, 8)
+        messages = [3., -2.]
+        v = pc.VectorLWE.encode_encrypt(self.sk_out, messages, int_encoder())
+        refreshed = v.keyswitch(ksk).bootstrap_all(self.bsk)
+        self.assertAllClose(refreshed.decrypt_decode(self.sk_out), messages, 0.5)
+        with self.assertRaises(ValueError):
+            pc.LWEKSK.for_bootstrap(self.sk, self.sk_out, self.bsk, 2, 8)
+
+    def test_repr_is_short_and_shows_the_parameters(self):
+        r = repr(self.ksk)
+        self.assertLess(len(r), 200)
+        self.assertIn('base_log', r)
+        self.assertIn('level', r)
+        self.assertGreater(len(self.ksk.describe()), len(r))
+
+    def test_getters_read_back_the_construction_parameters(self):
+        self.assertEqual((self.ksk.base_log, self.ksk.level), (2, 8))
+        self.assertEqual((self.ksk.dimension_before, self.ksk.dimension_after),
+                         (self.sk_out.dimension, self.sk.dimension))
+        self.assertEqual(self.ksk.variance, self.sk.get_variance())
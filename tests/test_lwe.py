@@ -0,0 +1,163 @@
+import pyconcrete as pc
+
+from common import TestCase, int_encoder, self_keys
+
+
+class TestLWE(TestCase):
+
+    def test_boolean_gates_truth_tables(self):
+        tables = {
+            'xor': lambda a, b: a ^ b,
+            'and_': lambda a, b: a & b,
+            'or_': lambda a, b: a | b,
+        }
+        for a in (False, True):
+            for b in (False, True):
+                x = pc.LWE.encrypt_bool(self.sk, a, 2)
+                y = pc.LWE.encrypt_bool(self.sk, b, 2)
+                for gate, truth in tables.items():
+                    with self.subTest(gate=gate, a=a, b=b):
+                        r = getattr(x, gate)(y, self.bsk)
+                        self.assertAlmostEqual(r.decrypt_decode(self.sk_out), float(truth(a, b)),
+                                               delta=0.25)
+
+    def test_is_trivial(self):
+        e = int_encoder()
+        self.assertTrue(pc.LWE.trivial_encrypt(3., e, self.sk.dimension).is_trivial())
+        self.assertFalse(pc.LWE.encode_encrypt(self.sk, 3., e).is_trivial())
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., 2.], e)
+        self.assertEqual(v.is_trivial(), [False, False])
+        self.assertEqual(v.sub_with_padding(v).is_trivial(), [True, True])
+
+    def test_pow_bootstrap_square_and_cube(self):
+        e = pc.Encoder(-2., 1., 2, 1)
+        outputs = {2: pc.Encoder(0., 4., 4, 1), 3: pc.Encoder(-8., 1., 4, 1)}
+        for x in (-2., -1., 0., 1.):
+            c = pc.LWE.encode_encrypt(self.sk, x, e)
+            for k, encoder_output in outputs.items():
+                with self.subTest(x=x, k=k):
+                    r = c.pow_bootstrap(self.bsk, k, encoder_output).decrypt_decode(self.sk_out)
+                    # the table is evaluated on the noisy input, so the error grows with x^k
+                    self.assertAlmostEqual(r, x ** k, delta=0.25 + 0.1 * abs(x ** k))
+        with self.assertRaises(ValueError):
+            c.pow_bootstrap(self.bsk, 3, outputs[2])
+        with self.assertRaises(ValueError):
+            c.pow_bootstrap(self.bsk, 9, outputs[2])
+
+    def test_select(self):
+        e = pc.Encoder(0., 1., 1, 2)
+        sk_self, bsk_self = self_keys()
+        # the bsk of the fixture changes the dimension and needs a ksk back, the other one does not
+        for sk, bsk, ksk, sk_out in ((self.sk, self.bsk, self.ksk, self.sk_out),
+                                     (sk_self, bsk_self, None, sk_self)):
+            a = pc.LWE.encode_encrypt(sk, 0., e)
+            b = pc.LWE.encode_encrypt(sk, 1., e)
+            for selector, expected in ((False, 1.), (True, 0.)):
+                with self.subTest(ksk=ksk is not None, selector=selector):
+                    s = pc.LWE.encrypt_bool(sk, selector, 1)
+                    r = pc.LWE.select(s, a, b, bsk, ksk)
+                    self.assertAlmostEqual(r.decrypt_decode(sk_out), expected, delta=0.5)
+        s = pc.LWE.encrypt_bool(self.sk, True, 1)
+        a = pc.LWE.encode_encrypt(self.sk, 0., e)
+        with self.assertRaises(ValueError):
+            pc.LWE.select(s, a, a, self.bsk)
+
+    def test_apply_table_with_a_custom_table(self):
+        e = pc.Encoder(0., 3., 2, 1)
+        table = [2., 0., 3., 1.]
+        for x in range(4):
+            with self.subTest(x=x):
+                c = pc.LWE.encode_encrypt(self.sk, float(x), e)
+                r = c.apply_table(self.bsk, table, e).decrypt_decode(self.sk_out)
+                self.assertAlmostEqual(r, table[x], delta=0.5)
+        with self.assertRaises(ValueError):
+            c.apply_table(self.bsk, table[:3], e)
+        with self.assertRaises(ValueError):
+            c.apply_table(self.bsk, [0., 0., 0., 9.], e)
+
+    def test_decrypt_raw_is_the_encoding_plus_noise(self):
+        e = int_encoder()
+        expected = e.encode([3.]).plaintexts[0]
+        for _ in range(5):
+            phase = pc.LWE.encode_encrypt(self.sk, 3., e).decrypt_raw(self.sk)
+            # signed distance on the 64-bit torus, bounded by 7 standard deviations of 2^-20
+            noise = (phase - expected + 2 ** 63) % 2 ** 64 - 2 ** 63
+            self.assertLess(abs(noise), 7 * 2 ** 44)
+        with self.assertRaises(ValueError):
+            pc.LWE.encode_encrypt(self.sk, 3., e).decrypt_raw(self.sk_out)
+
+    def test_bootstrap_multivalue_evaluates_two_functions(self):
+        e = pc.Encoder(0., 3., 2, 1)
+        outputs = [pc.Encoder(0., 4., 3, 1), pc.Encoder(-1., 3., 3, 1)]
+        funcs = [lambda x: x + 1., lambda x: 2. - x]
+        mv_lut = self.bsk.generate_multivalue_look_up_table(e, outputs, funcs)
+        for x in range(4):
+            with self.subTest(x=x):
+                c = pc.LWE.encode_encrypt(self.sk, float(x), e)
+                r = c.bootstrap_multivalue(self.bsk, mv_lut)
+                self.assertEqual(r.nb_ciphertexts, 2)
+                self.assertAllClose(r.decrypt_decode(self.sk_out), [f(x) for f in funcs], 0.5)
+        with self.assertRaises(ValueError):
+            self.bsk.generate_multivalue_look_up_table(e, outputs[:1], funcs)
+
+    def test_bootstrap_multivalue_checks_the_input_encoder(self):
+        e = pc.Encoder(0., 3., 2, 1)
+        mv_lut = self.bsk.generate_multivalue_look_up_table(e, [e], [lambda x: 3. - x])
+        self.assertEqual(mv_lut.encoder_input.as_tuple(), e.as_tuple())
+        self.assertEqual(mv_lut.nb_functions, 1)
+        # the same precision on another interval would decode the table wrongly
+        for other in (pc.Encoder(-4., 3., 2, 1), pc.Encoder(0., 3., 1, 1)):
+            with self.subTest(encoder=other.as_tuple()):
+                with self.assertRaises(ValueError):
+                    pc.LWE.encode_encrypt(self.sk, 1., other).bootstrap_multivalue(self.bsk, mv_lut)
+        # more padding than the table was built with is fine, the bootstrap keeps a single bit
+        padded = pc.Encoder(0., 3., 2, 3)
+        r = pc.LWE.encode_encrypt(self.sk, 1., padded).bootstrap_multivalue(self.bsk, mv_lut)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [2.], 0.5)
+
+    def test_bootstrap_multivalue_matches_bootstrap_with_function(self):
+        e, out = pc.Encoder(-4., 4., 3, 1), pc.Encoder(0., 16., 4, 1)
+        f = lambda x: x * x
+        mv_lut = self.bsk.generate_multivalue_look_up_table(e, [out], [f])
+        for x in (-3., 0., 2.):
+            with self.subTest(x=x):
+                c = pc.LWE.encode_encrypt(self.sk, x, e)
+                # the same table and the same bootstrap: the outputs are the same ciphertext
+                r = c.bootstrap_multivalue(self.bsk, mv_lut)
+                expected = c.bootstrap_with_function(self.bsk, f, out)
+                self.assertEqual(r.decrypt_decode(self.sk_out), [expected.decrypt_decode(self.sk_out)])
+                self.assertEqual(r.encoders[0].as_tuple(), expected.encoder.as_tuple())
+
+    def test_mod_constant(self):
+        out = pc.Encoder(0., 3., 2, 1)
+        for x, expected in [(7., 1.), (-1., 2.)]:
+            with self.subTest(x=x):
+                c = pc.LWE.encode_encrypt(self.sk, x, int_encoder())
+                r = c.mod_constant(self.bsk, 3, out)
+                self.assertAlmostEqual(r.decrypt_decode(self.sk_out), expected, delta=0.5)
+        with self.assertRaises(ValueError):
+            pc.LWE.encode_encrypt(self.sk, 1., int_encoder()).mod_constant(self.bsk, 0, out)
+
+    def test_decrypt_decode_int(self):
+        for x in (-5, 0, 6):
+            with self.subTest(x=x):
+                r = pc.LWE.encode_encrypt(self.sk, float(x), int_encoder()).decrypt_decode_int(self.sk)
+                self.assertIsInstance(r, int)
+                self.assertEqual(r, x)
+        # the grid of this encoder is 0, 1/3, 2/3, 1
+        c = pc.LWE.encode_encrypt(self.sk, 1. / 3., pc.Encoder(0., 1., 2, 1))
+        with self.assertRaises(ValueError):
+            c.decrypt_decode_int(self.sk)
+        self.assertEqual(c.decrypt_decode_int(self.sk, 0.4), 0)
+
+    def test_trivial_encrypt_shifts_a_real_ciphertext(self):
+        e = pc.Encoder(-8., 8., 5, 1)
+        for x, shift in [(1., 2.), (-3., 4.), (2., -5.)]:
+            with self.subTest(x=x, shift=shift):
+                t = pc.LWE.trivial_encrypt(shift, e, self.sk.dimension)
+                self.assertEqual(t.variance, 0.)
+                c = pc.LWE.encode_encrypt(self.sk, x, e)
+                r = c.add_with_padding(t)
+                self.assertAlmostEqual(r.decrypt_decode(self.sk), x + shift, delta=0.5)
+        with self.assertRaises(ValueError):
+            pc.LWE.trivial_encrypt(9., e, self.sk.dimension)
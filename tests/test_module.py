@@ -0,0 +1,41 @@
+import os
+import subprocess
+import sys
+
+import pyconcrete as pc
+
+from common import TestCase
+
+
+class TestModule(TestCase):
+
+    def test_benchmarks_are_positive_and_stable(self):
+        for bench, key in ((pc.benchmark_bootstrap, self.bsk), (pc.benchmark_keyswitch, self.ksk)):
+            with self.subTest(bench=bench.__name__):
+                first, second = bench(key, 5), bench(key, 5)
+                self.assertGreater(first, 0.)
+                self.assertGreater(second, 0.)
+                # loose bound, the machine may be loaded by other processes
+                self.assertLess(max(first, second) / min(first, second), 5.)
+        with self.assertRaises(ValueError):
+            pc.benchmark_bootstrap(self.bsk, 0)
+
+    def test_versions_are_non_empty_strings(self):
+        for version in (pc.version(), pc.concrete_version()):
+            self.assertIsInstance(version, str)
+            self.assertTrue(version)
+        # read from Cargo.lock at build time, within the ^0.1.11 requirement of Cargo.toml
+        self.assertRegex(pc.concrete_version(), r'^0\.1\.\d+$')
+
+    def test_set_num_threads_is_reflected_by_num_threads(self):
+        # the global pool can only be set once per process, use a fresh one
+        code = 'import pyconcrete as pc; pc.set_num_threads(3); pc.set_num_threads(3); print(pc.num_threads())'
+        env = dict(os.environ, PYTHONPATH=os.path.dirname(os.path.dirname(pc.__file__)))
+        out = subprocess.run([sys.executable, '-c', code], env=env, capture_output=True, text=True, check=True)
+        self.assertEqual(out.stdout.strip(), '3')
+        n = pc.num_threads()
+        pc.set_num_threads(n)
+        with self.assertRaises(ValueError):
+            pc.set_num_threads(n + 1)
+        with self.assertRaises(ValueError):
+            pc.set_num_threads(0)
@@ -0,0 +1,17 @@
+import pyconcrete as pc
+
+from common import TestCase
+
+
+class TestLWEParams(TestCase):
+
+    def test_recommend_grows_the_dimension_with_the_precision(self):
+        for security in (80, 128):
+            with self.subTest(security=security):
+                dimensions = [pc.LWEParams.recommend(p, 1, security).dimension for p in (2, 8, 16, 24)]
+                self.assertEqual(dimensions, sorted(dimensions))
+                self.assertLess(dimensions[0], dimensions[-1])
+        with self.assertRaisesRegex(ValueError, 'lower precision'):
+            pc.LWEParams.recommend(60, 1, 128)
+        with self.assertRaises(ValueError):
+            pc.LWEParams.recommend(4, 1, 100)
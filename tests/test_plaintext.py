@@ -0,0 +1,45 @@
+import pyconcrete as pc
+
+from common import TestCase, int_encoder
+
+
+class TestPlaintext(TestCase):
+
+    def test_encode_encrypt_chain(self):
+        messages = [1., -2., 3.]
+        v = int_encoder().encode(messages).encrypt(self.sk)
+        self.assertAllClose(v.decrypt_decode(self.sk), messages, 0.5)
+
+    def test_map_shifts_by_one(self):
+        messages = [1., -2., 3.]
+        p = int_encoder().encode(messages)
+        self.assertAllClose(p.map(lambda x: x + 1).decode(), [m + 1 for m in messages], 1e-9)
+        with self.assertRaises(ValueError):
+            p.map(lambda x: x + 10)
+
+    def test_zeros_with_encoders_decodes_each_value_with_its_encoder(self):
+        encoders = [int_encoder(), pc.Encoder(0., 1., 4, 1), pc.Encoder(100., 110., 5, 1)]
+        messages = [-3., 0.5, 104.]
+        p = pc.Plaintext.zeros_with_encoders(encoders)
+        self.assertEqual(p.nb_plaintexts, 3)
+        p.encode_inplace(messages)
+        self.assertAllClose(p.decode(), messages, 0.1)
+
+    def test_approx_eq_across_slightly_different_encoders(self):
+        messages = [0.3, 1.7, 2.2]
+        p = pc.Encoder(0., 4., 6, 1).encode(messages)
+        q = pc.Encoder(-0.1, 4.2, 6, 1).encode(messages)
+        self.assertNotEqual(p.plaintexts, q.plaintexts)
+        self.assertTrue(p.approx_eq(q, 0.1))
+        self.assertFalse(p.approx_eq(pc.Encoder(0., 4., 6, 1).encode([0.3, 1.7, 3.]), 0.1))
+        self.assertFalse(p.approx_eq(pc.Encoder(0., 4., 6, 1).encode(messages[:2]), 0.1))
+        with self.assertRaises(ValueError):
+            p.approx_eq(q, -1.)
+
+    def test_pad_to_appends_zeros(self):
+        messages = [1., -2., 3., 0., 5.]
+        p = int_encoder().encode(messages).pad_to(8, int_encoder())
+        self.assertEqual(p.nb_plaintexts, 8)
+        self.assertAllClose(p.decode(), messages + [0., 0., 0.], 1e-9)
+        with self.assertRaises(ValueError):
+            p.pad_to(4, int_encoder())
@@ -0,0 +1,64 @@
+import os
+import pickle
+import tempfile
+
+import pyconcrete as pc
+
+from common import TestCase, int_encoder
+
+
+class TestLWESecretKey(TestCase):
+
+    def test_adding_a_zero_encryption_keeps_the_value(self):
+        e = int_encoder()
+        messages = [3., -5.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, e)
+        zeros = self.sk.generate_zero_pool(e, len(messages))
+        self.assertEqual(zeros.nb_ciphertexts, len(messages))
+        self.assertAllClose(zeros.decrypt_decode(self.sk), [0., 0.], 0.5)
+        r = v.add_with_new_min(zeros, [e.get_min()] * len(messages))
+        self.assertAllClose(r.decrypt_decode(self.sk), messages, 0.5)
+
+    def test_derived_lwe_key_decrypts_bootstrapped_values(self):
+        lwe_key = self.rsk.to_lwe_secret_key()
+        self.assertEqual(lwe_key.dimension, self.rsk.get_lwe_dimension())
+        messages = [3., -5.]
+        # encrypted under the derived key, switched to the input key of the bsk and bootstrapped back
+        v = pc.VectorLWE.encode_encrypt(lwe_key, messages, int_encoder())
+        r = v.keyswitch(self.ksk).bootstrap_all(self.bsk)
+        self.assertAllClose(r.decrypt_decode(lwe_key), messages, 0.5)
+
+    def test_loading_a_truncated_file_raises(self):
+        with tempfile.TemporaryDirectory() as d:
+            path = os.path.join(d, 'sk')
+            self.sk.save(path)
+            with open(path, 'rb') as f:
+                content = f.read()
+            with open(path, 'wb') as f:
+                f.write(content[:len(content) // 2])
+            with self.assertRaises(ValueError):
+                pc.LWESecretKey.load(path)
+
+    def test_pickled_ciphertexts_do_not_contain_the_key(self):
+        def key_windows(key):
+            # 16 coefficients at a time, skipping the pickle header before the key payload; the
+            # windows with few ones look like the zeros of any structure and are left out
+            data = pickle.dumps(key)
+            payload = data[data.index(b'_from_bytes') + 20:]
+            windows = [payload[i:i + 128] for i in range(0, len(payload) - 128, 8)]
+            return [w for w in windows if w.count(1) >= 4]
+
+        e = pc.Encoder(-8., 8., 4, 1)
+        cases = [
+            (self.sk, pc.LWE.encode_encrypt(self.sk, 3., e)),
+            (self.sk, pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3.], e)),
+            (self.rsk, pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., -2., 3.], e)),
+        ]
+        for key, ct in cases:
+            with self.subTest(ciphertext=type(ct).__name__):
+                self.assertFalse(ct.contains_secret())
+                data = pickle.dumps(ct)
+                windows = key_windows(key)
+                self.assertGreater(len(windows), 100)
+                for window in windows:
+                    self.assertNotIn(window, data)
@@ -0,0 +1,595 @@
+import os
+import tempfile
+import unittest
+
+import pyconcrete as pc
+
+from common import TestCase, int_encoder, self_keys
+
+try:
+    import numpy
+except ImportError:
+    numpy = None
+
+
+class TestVectorLWE(TestCase):
+
+    def test_linear_with_padding_matches_cleartext(self):
+        messages, weights, bias = [1., 2., 3.], [0.5, -1., 2.], 1.5
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(0., 4., 6, 6))
+        r = v.linear_with_padding(weights, bias, 2., 5, 0.)
+        expected = sum(w * m for w, m in zip(weights, messages)) + bias
+        self.assertEqual(r.nb_ciphertexts, 1)
+        self.assertAllClose(r.decrypt_decode(self.sk), [expected], 0.25)
+
+    def test_bytes_per_ciphertext_round_trip(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        chunks = v.to_bytes_per_ciphertext()
+        self.assertEqual(len(chunks), len(messages))
+        w = pc.VectorLWE.from_bytes_per_ciphertext(chunks)
+        self.assertEqual(w.nb_ciphertexts, len(messages))
+        self.assertAllClose(w.decrypt_decode(self.sk), messages, 0.5)
+
+    def test_argmax_onehot_with_a_clear_maximum(self):
+        sk, bsk = self_keys()
+        # the runner-up is a single granularity away from the maximum
+        v = pc.VectorLWE.encode_encrypt(sk, [1., 3., 0., 2.], pc.Encoder(0., 3., 2, 2))
+        self.assertAllClose(v.argmax_onehot(bsk).decrypt_decode(sk), [0., 1., 0., 0.], 0.25)
+
+    def test_bootstrap_all_is_independent_of_max_threads(self):
+        messages = [-6., -3., 0., 2., 5.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        reference = v.bootstrap_all(self.bsk).decrypt_decode(self.sk_out)
+        self.assertAllClose(reference, messages, 0.5)
+        for max_threads in (1, 2, 3, 8):
+            with self.subTest(max_threads=max_threads):
+                r = v.bootstrap_all(self.bsk, max_threads=max_threads)
+                self.assertEqual(r.decrypt_decode(self.sk_out), reference)
+
+    def test_setters_keep_the_buffer_consistent(self):
+        messages = [1., 2.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        with self.assertRaises(ValueError):
+            v.dimension = v.dimension + 1
+        v.nb_ciphertexts = 3
+        self.assertEqual((v.nb_ciphertexts, len(v.encoders), len(v.variances)), (3, 3, 3))
+        self.assertAlmostEqual(v.decrypt_decode_nth(self.sk, 1), 2., delta=0.5)
+        v.nb_ciphertexts = 1
+        self.assertAllClose(v.decrypt_decode(self.sk), messages[:1], 0.5)
+
+    def test_running_max_bootstrap_matches_cleartext(self):
+        sk, bsk = self_keys()
+        messages = [1., 0., 3., 2.]
+        v = pc.VectorLWE.encode_encrypt(sk, messages, pc.Encoder(0., 3., 2, 2))
+        expected = [max(messages[:i + 1]) for i in range(len(messages))]
+        self.assertAllClose(v.running_max_bootstrap(bsk).decrypt_decode(sk), expected, 0.25)
+
+    def test_round_all_and_round_nth(self):
+        # a tight interval keeps the bootstrap noise well below the distance to the .5 boundaries
+        v = pc.VectorLWE.encode_encrypt(self.sk, [2.7, -1.4], pc.Encoder(-1.6, 2.9, 4, 1))
+        encoder_output = pc.Encoder(-4., 3., 3, 1)
+        self.assertAllClose(v.round_all(self.bsk, encoder_output).decrypt_decode(self.sk_out),
+                            [3., -1.], 0.25)
+        self.assertAllClose(v.round_nth(self.bsk, 1, encoder_output).decrypt_decode(self.sk_out),
+                            [-1.], 0.25)
+
+    @unittest.skipUnless(numpy, "numpy is not installed")
+    def test_raw_ciphertexts_numpy_of_a_trivial_encryption(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [3., -5.], pc.Encoder(-8., 7., 4, 2))
+        # subtracting a ciphertext from itself cancels the mask and leaves an encoded 0
+        t = v.sub_with_padding(v)
+        self.assertEqual(t.is_trivial(), [True, True])
+        raw = t.raw_ciphertexts_numpy()
+        self.assertEqual(raw.shape, (2, t.dimension + 1))
+        self.assertEqual(raw.dtype, numpy.uint64)
+        self.assertTrue((raw[:, :-1] == 0).all())
+        self.assertEqual(list(raw[:, -1]),
+                         [e.encode_single(0.).plaintexts[0] for e in t.encoders])
+
+    def test_decrypt_honors_the_round_flag_of_each_encoder(self):
+        encoders = [pc.Encoder.new_rounding_context(0., 7., 3, 1), pc.Encoder(0., 8., 6, 1)]
+        v = pc.VectorLWE.encode_encrypt_several_encoders(self.sk, [2.2, 2.2], encoders)
+        rounded, unrounded = v.decrypt(self.sk)
+        self.assertEqual(rounded, 2.)
+        self.assertNotEqual(unrounded, 2.)
+        self.assertAlmostEqual(unrounded, 2.2, delta=0.1)
+
+    def test_histogram_matches_cleartext_counts(self):
+        data = [0., 1., 1., 3., 5., 6.]
+        edges = [-8., -0.5, 2.5, 4.5, 7.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, data, int_encoder())
+        expected = [sum(lo <= x < hi for x in data) for lo, hi in zip(edges, edges[1:])]
+        h = v.histogram(self.bsk, edges)
+        self.assertEqual(h.nb_ciphertexts, len(edges) - 1)
+        self.assertAllClose(h.decrypt_decode(self.sk_out), expected, 0.5)
+        with self.assertRaises(ValueError):
+            v.histogram(self.bsk, [1., 0.])
+
+    def test_permute_then_inverse_permute_is_identity(self):
+        messages = [1., 2., 3., -4.]
+        perm = [2, 0, 3, 1]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        p = v.permute(perm)
+        self.assertAllClose(p.decrypt_decode(self.sk), [messages[i] for i in perm], 0.5)
+        self.assertAllClose(p.inverse_permute(perm).decrypt_decode(self.sk), messages, 0.5)
+        with self.assertRaises(ValueError):
+            v.permute([0, 0, 1, 2])
+
+    def test_mul_constant_static_encoder_range_check(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., 2.], pc.Encoder(-4., 4., 4, 1))
+        self.assertAllClose(v.mul_constant_static_encoder([1, -1]).decrypt_decode(self.sk),
+                            [1., -2.], 0.5)
+        with self.assertRaisesRegex(ValueError, r"\[1\]"):
+            v.mul_constant_static_encoder([1, 2])
+
+    def test_matmul_matches_cleartext(self):
+        x = [1., 2., 3.]
+        weights = [[1., 0., -1.], [0.5, 1., 2.]]
+        v = pc.VectorLWE.encode_encrypt(self.sk, x, pc.Encoder(0., 4., 5, 8))
+        r = v @ weights
+        expected = [sum(w * m for w, m in zip(row, x)) for row in weights]
+        self.assertAllClose(r.decrypt_decode(self.sk), expected, r.encoders[0].get_granularity() / 2)
+        with self.assertRaises(ValueError):
+            v @ [[1., 2.]]
+
+    def test_encode_encrypt_names_the_out_of_range_message(self):
+        e = int_encoder()
+        with self.assertRaisesRegex(ValueError, r"message 9(\.0)? at index 2 is outside"):
+            pc.VectorLWE.encode_encrypt(self.sk, [1., 2., 9., 10.], e)
+        # contains agrees with the encoding: max < 7.25 < min + delta is still accepted
+        # (not 7.5, which the decoding may round up to 8 and wrap around to -8)
+        self.assertTrue(e.contains(7.25))
+        self.assertFalse(e.contains(e.get_min() + e.delta))
+        v = pc.VectorLWE.encode_encrypt(self.sk, [7.25], e)
+        self.assertAllClose(v.decrypt_decode(self.sk), [7.25], 0.5)
+
+    def test_opposite_negates_every_element(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        negated = [-m for m in messages]
+        self.assertAllClose(v.opposite().decrypt_decode(self.sk), negated, 0.5)
+        self.assertAllClose((-v).decrypt_decode(self.sk), negated, 0.5)
+        v.opposite_inplace()
+        self.assertAllClose(v.decrypt_decode(self.sk), negated, 0.5)
+
+    def test_sum_of_partials(self):
+        e = pc.Encoder(0., 2., 4, 3)
+        partials = [0.5, 1., 1.5]
+        parts = [pc.VectorLWE.encode_encrypt(self.sk, [m], e) for m in partials]
+        # the output keeps the size of the input interval, so its min has to fit the total
+        total = pc.VectorLWE.sum_of(parts, 2.)
+        self.assertEqual(total.nb_ciphertexts, 1)
+        self.assertAllClose(total.decrypt_decode(self.sk), [sum(partials)], 0.1)
+        other = pc.VectorLWE.encode_encrypt(self.sk, [1.], pc.Encoder(0., 4., 4, 3))
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.sum_of([parts[0], other], 2.)
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.sum_of([], 2.)
+
+    def test_scale_decoding_by_ten(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        scaled = v.scale_decoding(10.)
+        self.assertAllClose(scaled.decrypt_decode(self.sk), [10. * m for m in messages], 0.5)
+        self.assertEqual([e.nb_bit_padding for e in scaled.encoders],
+                         [e.nb_bit_padding for e in v.encoders])
+        with self.assertRaises(ValueError):
+            v.scale_decoding(0.)
+
+    def test_encrypt_inplace_resizes_a_mismatched_zero(self):
+        messages = [1., -2., 3.]
+        for size in (1, 3, 5):
+            with self.subTest(size=size):
+                v = pc.VectorLWE.zero(self.sk.dimension, size)
+                v.encrypt_inplace(self.sk, int_encoder().encode(messages))
+                self.assertEqual(v.nb_ciphertexts, len(messages))
+                self.assertEqual((len(v.encoders), len(v.variances)), (3, 3))
+                self.assertAllClose(v.decrypt_decode(self.sk), messages, 0.5)
+
+    def test_decrypt_decode_nth_matches_the_full_decryption(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3., 0.], int_encoder())
+        full = v.decrypt_decode(self.sk)
+        for n in range(v.nb_ciphertexts):
+            self.assertEqual(v.decrypt_decode_nth(self.sk, n), full[n])
+        with self.assertRaises(IndexError):
+            v.decrypt_decode_nth(self.sk, 4)
+
+    def test_repeated_additions_exhaust_the_noise_budget(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [0.5, 0.25], pc.Encoder(-1., 1., 10, 1))
+        self.assertEqual(v.min_noise_budget_bits(), 10)
+        v.assert_budget(10)
+        # each centered addition doubles the variance, half a bit of noise more
+        for _ in range(25):
+            v = v.add_centered(v)
+        self.assertLess(v.min_noise_budget_bits(), 6)
+        with self.assertRaises(ValueError):
+            v.assert_budget(6)
+
+    def test_zip_add_matches_the_cleartext_sums(self):
+        a, b = [1., -2., 3.], [2., 1., -4.]
+        va = pc.VectorLWE.encode_encrypt(self.sk, a, int_encoder())
+        vb = pc.VectorLWE.encode_encrypt(self.sk, b, int_encoder())
+        s = va.zip_add(vb, [-8., -8., -8.])
+        self.assertAllClose(s.decrypt_decode(self.sk), [x + y for x, y in zip(a, b)], 0.5)
+        with self.assertRaises(ValueError):
+            va.zip_add(vb, [-8., -8.])
+        with self.assertRaises(ValueError):
+            va.zip_add(pc.VectorLWE.encode_encrypt(self.sk, a[:2], int_encoder()), [-8.] * 3)
+
+    def test_encode_encrypt_with_std_lower_noise_keeps_more_precision(self):
+        e = pc.Encoder(-8., 7., 6, 8)
+        budgets = [pc.VectorLWE.encode_encrypt_with_std(self.sk, [1.], e, s).min_noise_budget_bits()
+                   for s in (-30, -12, -8)]
+        self.assertEqual(budgets, sorted(budgets, reverse=True))
+        self.assertGreater(budgets[0], budgets[-1])
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt_with_std(self.sk, [1.], e, -70)
+
+    def test_inplace_operators_update_the_same_object(self):
+        e = pc.Encoder(-8., 7., 4, 2)
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2.], e)
+        alias = v
+        v += pc.VectorLWE.encode_encrypt(self.sk, [2., 1.], e)
+        self.assertIs(v, alias)
+        self.assertAllClose(alias.decrypt_decode(self.sk), [3., -1.], 0.5)
+        v -= [1., 1.]
+        self.assertIs(v, alias)
+        self.assertAllClose(alias.decrypt_decode(self.sk), [2., -2.], 0.5)
+        v += [1., 2.]
+        self.assertIs(v, alias)
+        self.assertAllClose(alias.decrypt_decode(self.sk), [3., 0.], 0.5)
+
+    def test_encrypt_inplace_into_a_wrong_dimension_raises(self):
+        v = pc.VectorLWE.zero(self.sk.dimension + 1, 3)
+        with self.assertRaisesRegex(ValueError, 'DimensionError.*{}.*{}'.format(
+                self.sk.dimension, self.sk.dimension + 1)):
+            v.encrypt_inplace(self.sk, int_encoder().encode([1., -2., 3.]))
+
+    def test_indexing_with_slices_and_negative_indices(self):
+        messages = [-4., -2., 0., 1., 3., 5.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        cases = {
+            'forward': (slice(1, 4), messages[1:4]),
+            'negative': (slice(-3, None), messages[-3:]),
+            'strided': (slice(0, None, 2), messages[::2]),
+            'reversed': (slice(None, None, -2), messages[::-2]),
+            'integer': (-1, [messages[-1]]),
+        }
+        for name, (index, expected) in cases.items():
+            with self.subTest(name):
+                self.assertAllClose(v[index].decrypt_decode(self.sk), expected, 0.5)
+        with self.assertRaises(IndexError):
+            v[6]
+        with self.assertRaises(IndexError):
+            v[-7]
+
+    def test_save_load_round_trip_with_and_without_compression(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        with tempfile.TemporaryDirectory() as d:
+            for compress in (False, True):
+                with self.subTest(compress=compress):
+                    path = os.path.join(d, 'v{}'.format(int(compress)))
+                    v.save(path, compress)
+                    with open(path, 'rb') as f:
+                        self.assertEqual(f.read(2) == b'\x1f\x8b', compress)
+                    self.assertAllClose(pc.VectorLWE.load(path).decrypt_decode(self.sk), messages, 0.5)
+                    chunks = v.to_bytes_per_ciphertext(compress)
+                    w = pc.VectorLWE.from_bytes_per_ciphertext(chunks)
+                    self.assertAllClose(w.decrypt_decode(self.sk), messages, 0.5)
+
+    def test_cmux_picks_the_elements_of_a_mixed_selector(self):
+        e = pc.Encoder(0., 1., 1, 2)
+        pattern = [1., 0., 0., 1.]
+        selectors = pc.VectorLWE.encode_encrypt(self.sk, pattern, pc.Encoder(0., 1., 2, 1))
+        a = pc.VectorLWE.encode_encrypt(self.sk, [0., 1., 0., 1.], e)
+        b = pc.VectorLWE.encode_encrypt(self.sk, [1., 0., 1., 1.], e)
+        r = pc.VectorLWE.cmux(selectors, a, b, self.bsk, self.ksk, chunk_size=3)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [0., 0., 1., 1.], 0.5)
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.cmux(selectors, a, b[:3], self.bsk, self.ksk)
+
+    def test_byte_sizes_match_the_serialized_lengths(self):
+        # the serialization is a fixed header followed by the masks and bodies
+        messages = [1., -2., 3.]
+        small = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        large = pc.VectorLWE.encode_encrypt(self.sk_out, messages, int_encoder())
+        self.assertEqual(small.total_size_bytes(), 3 * small.ciphertext_size_bytes())
+        self.assertEqual(small.ciphertext_size_bytes(), 8 * (self.sk.dimension + 1))
+        sizes = [len(v.__reduce__()[1][0]) for v in (small, large)]
+        self.assertEqual(sizes[1] - sizes[0], large.total_size_bytes() - small.total_size_bytes())
+        self.assertGreater(sizes[0], small.total_size_bytes())
+
+    def test_deinterleave_interleave_round_trip(self):
+        messages = [-6., -4., -2., 0., 2., 4.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        channels = v.deinterleave(3)
+        self.assertEqual(len(channels), 3)
+        for c, channel in enumerate(channels):
+            self.assertAllClose(channel.decrypt_decode(self.sk), messages[c::3], 0.5)
+        w = pc.VectorLWE.interleave(channels)
+        self.assertAllClose(w.decrypt_decode(self.sk), messages, 0.5)
+        with self.assertRaises(ValueError):
+            v.deinterleave(4)
+
+    def test_encode_encrypt_matrix_decrypts_back_into_the_shape(self):
+        matrix = [[1., -2., 3.], [0., 4., -5.]]
+        v, (rows, cols) = pc.VectorLWE.encode_encrypt_matrix(self.sk, matrix, int_encoder())
+        self.assertEqual((rows, cols), (2, 3))
+        flat = v.decrypt_decode(self.sk)
+        for r in range(rows):
+            self.assertAllClose(flat[r * cols:(r + 1) * cols], matrix[r], 0.5)
+        for bad in ([], [[]], [[1., 2.], [3.]]):
+            with self.assertRaises(ValueError):
+                pc.VectorLWE.encode_encrypt_matrix(self.sk, bad, int_encoder())
+
+    def test_align_encoders_with_then_add(self):
+        # the deltas are 16 and 8, one bit of padding of the second one becomes a message bit
+        x = pc.VectorLWE.encode_encrypt(self.sk, [3., -6.], pc.Encoder(-8., 7., 4, 3))
+        y = pc.VectorLWE.encode_encrypt(self.sk, [-2., 1.5], pc.Encoder(-4., 3.5, 4, 3))
+        with self.assertRaises(ValueError):
+            x.add_with_padding(y)
+        xa, ya = x.align_encoders_with(y)
+        self.assertAllClose(xa.decrypt_decode(self.sk), [3., -6.], 0.5)
+        self.assertAllClose(ya.decrypt_decode(self.sk), [-2., 1.5], 0.5)
+        self.assertAllClose(xa.add_with_padding(ya).decrypt_decode(self.sk), [1., -4.5], 0.5)
+        z = pc.VectorLWE.encode_encrypt(self.sk, [0., 1.], pc.Encoder(-4., 0.8, 4, 3))
+        with self.assertRaises(ValueError):
+            x.align_encoders_with(z)
+
+    def test_refresh_all_restores_the_noise_budget(self):
+        messages = [4., -6.]
+        noisy = pc.VectorLWE.encode_encrypt_with_std(self.sk_out, messages, int_encoder(), -9)
+        refreshed = noisy.refresh_all(self.ksk, self.bsk)
+        self.assertAllClose(refreshed.decrypt_decode(self.sk_out), messages, 0.5)
+        for before, after in zip(noisy.variances, refreshed.variances):
+            self.assertLess(after, before / 2 ** 10)
+        # the same additions exhaust the budget of the noisy vector but not of the refreshed one
+        for _ in range(7):
+            noisy = noisy.add_centered(noisy)
+            refreshed = refreshed.add_centered(refreshed)
+        self.assertLess(noisy.min_noise_budget_bits(), refreshed.min_noise_budget_bits())
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder()).refresh_all(self.ksk, self.bsk)
+
+    def test_add_scalar_shifts_every_element(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        self.assertAllClose(v.add_scalar(2.5).decrypt_decode(self.sk), [m + 2.5 for m in messages], 0.5)
+
+    def test_encode_encrypt_debug_references_are_the_decoded_encodings(self):
+        messages = [1.2, -2.7, 3.4]
+        e = pc.Encoder(-4., 4., 3, 1)
+        v, reference = pc.VectorLWE.encode_encrypt_debug(self.sk, messages, e)
+        self.assertEqual(reference, e.encode(messages).decode())
+        self.assertAllClose(v.decrypt_decode(self.sk), reference, 0.5)
+
+    def test_count_nonzero_over_a_known_vector(self):
+        messages = [0., 3., 0., -2., 5., 0.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        count = v.count_nonzero(self.bsk, 0.5)
+        self.assertEqual(count.nb_ciphertexts, 1)
+        self.assertAllClose(count.decrypt_decode(self.sk_out), [3.], 0.5)
+
+    def test_padding_bits_drop_after_add_with_padding(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2.], pc.Encoder(-8., 7., 4, 2))
+        self.assertEqual(v.padding_bits(), [2, 2])
+        self.assertTrue(v.has_padding(2))
+        s = v.add_with_padding(v)
+        self.assertEqual(s.padding_bits(), [1, 1])
+        self.assertFalse(s.has_padding(2))
+        c = pc.LWE.encode_encrypt(self.sk, 1., pc.Encoder(-8., 7., 4, 2))
+        self.assertEqual(c.padding_bits(), 2)
+        self.assertEqual(c.add_with_padding(c).padding_bits(), 1)
+        self.assertTrue(c.add_with_padding(c).has_padding(1))
+
+    def test_map_squares_each_element(self):
+        messages = [-2., -1., 0., 1.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-2., 1., 2, 1))
+        r = v.map(self.bsk, lambda x: x * x, pc.Encoder(0., 4., 4, 1), chunk_size=3)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [m * m for m in messages], 0.5)
+
+    def test_loading_a_truncated_file_raises(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3.], int_encoder())
+        with tempfile.TemporaryDirectory() as d:
+            for compress in (False, True):
+                with self.subTest(compress=compress):
+                    path = os.path.join(d, 'v')
+                    v.save(path, compress)
+                    with open(path, 'rb') as f:
+                        content = f.read()
+                    with open(path, 'wb') as f:
+                        f.write(content[:len(content) // 2])
+                    with self.assertRaises(ValueError):
+                        pc.VectorLWE.load(path)
+            with self.assertRaises(ValueError):
+                pc.VectorLWE.load(os.path.join(d, 'missing'))
+
+    def test_zeros_like_matches_the_source(self):
+        v = pc.VectorLWE.encode_encrypt_several_encoders(
+            self.sk, [1., 0.5], [int_encoder(), pc.Encoder(0., 1., 3, 2)])
+        z = v.zeros_like()
+        self.assertEqual((z.dimension, z.nb_ciphertexts), (v.dimension, v.nb_ciphertexts))
+        self.assertEqual([e.as_tuple() for e in z.encoders], [e.as_tuple() for e in v.encoders])
+        self.assertEqual(z.variances, [0., 0.])
+
+    def test_encrypt_checked_catches_a_dimension_mismatch(self):
+        p = int_encoder().encode([1., -2.])
+        v = pc.VectorLWE.encrypt_checked(self.sk, p, self.sk.dimension)
+        self.assertAllClose(v.decrypt_decode(self.sk), [1., -2.], 0.5)
+        with self.assertRaisesRegex(ValueError, 'DimensionError'):
+            pc.VectorLWE.encrypt_checked(self.sk, p, self.sk_out.dimension)
+
+    def test_normalize_by_cleartext_sum_on_a_positive_vector(self):
+        messages = [1., 2., 1.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(0., 3., 4, 4))
+        r = v.normalize_by_cleartext_sum(self.bsk, self.ksk)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [m / sum(messages) for m in messages], 0.1)
+        negative = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-1., 3., 4, 4))
+        with self.assertRaises(ValueError):
+            negative.normalize_by_cleartext_sum(self.bsk, self.ksk)
+
+    def test_decrypt_contains_finds_and_misses_a_value(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3.], int_encoder())
+        self.assertTrue(v.decrypt_contains(self.sk, -2., 0.5))
+        self.assertFalse(v.decrypt_contains(self.sk, 5., 0.5))
+
+    def test_affine_activation_matches_the_composed_function(self):
+        messages = [-2., -1., 0., 1.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-2., 1., 2, 1))
+        out = pc.Encoder(0., 6., 4, 1)
+        r = v.affine_activation(self.bsk, 2., -1., lambda x: abs(x), out)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [abs(2. * m - 1.) for m in messages], 0.5)
+        batched = v.affine_activation(self.bsk, 2., -1., lambda x: abs(x), out, max_threads=1, chunk_size=3)
+        self.assertEqual(batched.decrypt_decode(self.sk_out), r.decrypt_decode(self.sk_out))
+
+    def test_bits_round_trip(self):
+        for value, nb_bits in ((0, 1), (1, 1), (0b1011001, 8), (2 ** 64 - 1, 64)):
+            with self.subTest(value=value, nb_bits=nb_bits):
+                v = pc.VectorLWE.encode_encrypt_bits(self.sk, value, nb_bits, 1)
+                self.assertEqual(v.nb_ciphertexts, nb_bits)
+                self.assertEqual(v.decrypt_bits(self.sk), value)
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt_bits(self.sk, 256, 8, 1)
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt_bits(self.sk, 1, 65, 1)
+
+    def test_bootstrap_indexed_relu_on_even_and_identity_on_odd(self):
+        messages = [-3., -2., 2., -1.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        r = v.bootstrap_indexed(self.bsk, lambda i, x: max(x, 0.) if i % 2 == 0 else x, [int_encoder()] * 4)
+        self.assertAllClose(r.decrypt_decode(self.sk_out), [0., -2., 2., -1.], 0.5)
+        with self.assertRaises(ValueError):
+            v.bootstrap_indexed(self.bsk, lambda i, x: x, [int_encoder()] * 3)
+
+    def test_predicted_add_variance_matches_the_measured_noise(self):
+        n, e = 400, pc.Encoder(-8., 7., 4, 2)
+        a = pc.VectorLWE.encode_encrypt(self.sk, [1.] * n, e)
+        b = pc.VectorLWE.encode_encrypt(self.sk, [2.] * n, e)
+        predicted = a.predict_add_variance(b)
+        s = a.add_with_padding(b)
+        self.assertAllClose(predicted, s.variances, 1e-20)
+        # the decoded error is the noise on the torus times delta * 2^padding
+        _, delta, _, padding, _ = s.encoders[0].as_tuple()
+        measured = sum((x - 3.) ** 2 for x in s.decrypt(self.sk)) / n / (delta * 2 ** padding) ** 2
+        self.assertLess(max(measured, predicted[0]) / min(measured, predicted[0]), 2.)
+
+    @unittest.skipUnless(numpy, "numpy is not installed")
+    def test_decrypt_decode_masked_flags_an_over_noisy_element(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3.], int_encoder())
+        # pretend the first ciphertext went through too many operations
+        v.variances = [2. ** -8] + v.variances[1:]
+        self.assertLess(v.min_noise_budget_bits(), 4)
+        r = v.decrypt_decode_masked(self.sk, 4)
+        self.assertTrue(numpy.isnan(r[0]))
+        self.assertTrue(numpy.all(numpy.isfinite(r[1:])))
+        self.assertAllClose(list(r[1:]), [-2., 3.], 0.5)
+
+    def test_decrypting_with_a_mismatched_key_raises_when_checked(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2.], int_encoder())
+        self.assertEqual(v.key_fingerprint, self.sk.fingerprint())
+        other = pc.LWESecretKey(pc.LWEParams(256, -20))
+        with self.assertRaises(ValueError):
+            v.decrypt_decode(other, key_check="raise")
+        v.decrypt_decode(other, key_check="ignore")
+        self.assertAllClose(v.decrypt_decode(self.sk, key_check="raise"), [1., -2.], 0.5)
+        # the fingerprint survives a save/load round trip
+        with tempfile.TemporaryDirectory() as d:
+            path = os.path.join(d, "v.json")
+            v.save(path)
+            w = pc.VectorLWE.load(path)
+        with self.assertRaises(ValueError):
+            w.decrypt_decode(other, key_check="raise")
+
+    def test_rechunk_then_concat_recovers_the_original(self):
+        v = pc.VectorLWE.encode_encrypt(self.sk, [1., -2., 3., -4., 5.], int_encoder())
+        parts = v.rechunk(2)
+        self.assertEqual([p.nb_ciphertexts for p in parts], [2, 2, 1])
+        w = pc.VectorLWE.concat(parts)
+        self.assertEqual(w.decrypt(self.sk), v.decrypt(self.sk))
+        self.assertEqual(w.variances, v.variances)
+        self.assertAllClose(w.decrypt_decode(self.sk), [1., -2., 3., -4., 5.], 0.5)
+        with self.assertRaises(ValueError):
+            v.rechunk(0)
+
+    def test_reshape_and_get_2d(self):
+        messages = [1., -2., 3., -4., 5., -6.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        self.assertIsNone(v.shape())
+        m = v.reshape(2, 3)
+        self.assertEqual(m.shape(), (2, 3))
+        self.assertAlmostEqual(m.get_2d(1, 2).decrypt_decode(self.sk), -6., delta=0.5)
+        self.assertAlmostEqual(m.get_2d(0, 1).decrypt_decode(self.sk), -2., delta=0.5)
+        self.assertEqual(m.zeros_like().shape(), (2, 3))
+        with self.assertRaises(IndexError):
+            m.get_2d(2, 0)
+        with self.assertRaises(ValueError):
+            v.get_2d(0, 0)
+        with self.assertRaises(ValueError):
+            v.reshape(4, 2)
+        # resizing drops the shape, which would no longer match
+        m.encrypt_inplace(self.sk, int_encoder().encode([1., 2., 3., 4.]))
+        self.assertIsNone(m.shape())
+        with self.assertRaises(ValueError):
+            m.get_2d(1, 1)
+
+    def test_chunked_bootstrap_all_matches_the_unchunked_one(self):
+        messages = [-6., -3., -1., 0., 2., 5., 7.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        whole = v.bootstrap_all(self.bsk, max_threads=2)
+        chunked = v.bootstrap_all(self.bsk, max_threads=2, chunk_size=3)
+        # the bootstrap is deterministic
+        self.assertEqual(chunked.decrypt(self.sk_out), whole.decrypt(self.sk_out))
+        self.assertAllClose(chunked.decrypt_decode(self.sk_out), messages, 0.5)
+        with self.assertRaises(ValueError):
+            v.bootstrap_all(self.bsk, chunk_size=0)
+
+    def test_decrypt_decode_of_a_large_vector(self):
+        # decrypt_decode reads the encoders in place; before, cloning one encoder per ciphertext
+        # made up a noticeable part of the decryption time of vectors this large
+        messages = [float(i % 15 - 7) for i in range(20000)]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, int_encoder())
+        self.assertAllClose(v.decrypt_decode(self.sk), messages, 0.5)
+        self.assertAlmostEqual(v.decrypt_decode_nth(self.sk, 19999), messages[19999], delta=0.5)
+
+    def test_group_sum_with_padding(self):
+        messages = [1., -3., 3., 3., -5., 1.]
+        v = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-8., 7., 4, 2))
+        r = v.group_sum_with_padding(2, [-16.] * 3)
+        self.assertEqual(r.nb_ciphertexts, 3)
+        # the sums are even, on the grid of the doubled interval
+        expected = [messages[i] + messages[i + 1] for i in range(0, 6, 2)]
+        self.assertAllClose(r.decrypt_decode(self.sk), expected, 0.5)
+        with self.assertRaises(ValueError):
+            v.group_sum_with_padding(4, [-16.])
+        with self.assertRaises(ValueError):
+            v.group_sum_with_padding(2, [-16.] * 2)
+
+    def test_encode_encrypt_iter_from_a_generator(self):
+        v = pc.VectorLWE.encode_encrypt_iter(self.sk, (x / 2. for x in range(-6, 7, 3)), int_encoder())
+        self.assertEqual(v.nb_ciphertexts, 5)
+        self.assertAllClose(v.decrypt_decode(self.sk), [-3., -1.5, 0., 1.5, 3.], 0.5)
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt_iter(self.sk, iter([1., 20.]), int_encoder())
+        with self.assertRaises(ValueError):
+            pc.VectorLWE.encode_encrypt_iter(self.sk, (x for x in []), int_encoder())
+        with self.assertRaises(TypeError):
+            pc.VectorLWE.encode_encrypt_iter(self.sk, 3., int_encoder())
+
+    def test_add_refreshing_keeps_a_long_chain_correct(self):
+        acc = pc.VectorLWE.encode_encrypt(self.sk_out, [1., 2.], pc.Encoder(0., 4., 4, 2))
+        expected = [1., 2.]
+        for _ in range(4):
+            # a fresh operand on the interval of the sum, with 2 bits of padding
+            _, delta, precision, _, round_ = acc.encoders[0].as_tuple()
+            messages = [delta / 8., delta / 4.]
+            operand = pc.VectorLWE.encode_encrypt(
+                self.sk_out, messages, pc.Encoder.from_tuple((0., delta, precision, 2, round_)))
+            acc = acc.add_refreshing(operand, self.ksk, self.bsk)
+            expected = [x + m for x, m in zip(expected, messages)]
+            self.assertAllClose(acc.decrypt_decode(self.sk_out), expected, acc.encoders[0].get_granularity())
+        # the first sum has 1 bit of padding left, so every later addition refreshed it first
+        self.assertIsNone(acc.key_fingerprint)
+        with self.assertRaises(ValueError):
+            acc.add_refreshing(operand, self.ksk, self.bsk, refresh_padding=1)
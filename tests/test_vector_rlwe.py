@@ -0,0 +1,105 @@
+import os
+import pickle
+import tempfile
+import unittest
+
+import pyconcrete as pc
+
+from common import TestCase
+
+try:
+    import numpy
+except ImportError:
+    numpy = None
+
+
+class TestVectorRLWE(TestCase):
+
+    def test_get_encoder_at_reads_a_packed_slot(self):
+        encoder = pc.Encoder(0., 4., 4, 1)
+        v = pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., 2., 3.], encoder)
+        e = v.get_encoder_at(0, 1)
+        self.assertEqual((e.get_min(), e.nb_bit_precision, e.nb_bit_padding),
+                         (encoder.get_min(), encoder.nb_bit_precision, encoder.nb_bit_padding))
+        self.assertAlmostEqual(e.get_max(), encoder.get_max())
+        with self.assertRaises(IndexError):
+            v.get_encoder_at(0, v.polynomial_size)
+
+    def test_pp_runs(self):
+        v = pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., 2.], pc.Encoder(0., 4., 4, 1))
+        v.pp()
+
+    @unittest.skipUnless(numpy, "numpy is not installed")
+    def test_coefficients_numpy_shape(self):
+        v = pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., 2.], pc.Encoder(0., 4., 4, 1))
+        coefficients = v.coefficients_numpy(self.rsk)
+        self.assertEqual(coefficients.shape, (v.nb_ciphertexts, v.polynomial_size))
+
+    def test_opposite_negates_the_slots(self):
+        messages = [1., -2., 3.]
+        v = pc.VectorRLWE.encode_encrypt_packed(self.rsk, messages, pc.Encoder(-4., 4., 4, 1))
+        self.assertAllClose(v.opposite().decrypt_decode(self.rsk), [-m for m in messages], 0.5)
+        v.opposite_inplace()
+        self.assertAllClose(v.decrypt_decode(self.rsk), [-m for m in messages], 0.5)
+
+    def test_byte_sizes_match_the_serialized_lengths(self):
+        # the serialization is a header with one encoder and variance per slot, followed by the
+        # polynomials, so only the dimension changes between the two
+        encoder = pc.Encoder(0., 4., 4, 1)
+        small = pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., 2.], encoder)
+        rsk_large = pc.RLWESecretKey(pc.RLWEParams(512, 2, -30))
+        large = pc.VectorRLWE.encode_encrypt_packed(rsk_large, [1., 2.], encoder)
+        self.assertEqual(small.ciphertext_size_bytes(), 8 * 512 * 2)
+        sizes = [len(v.__reduce__()[1][0]) for v in (small, large)]
+        self.assertEqual(sizes[1] - sizes[0], large.total_size_bytes() - small.total_size_bytes())
+        self.assertGreater(sizes[0], small.total_size_bytes())
+
+    def test_valid_mask_after_packing_five_messages(self):
+        rsk = pc.RLWESecretKey(pc.RLWEParams(1024, 1, -30))
+        v = pc.VectorRLWE.encode_encrypt_packed(rsk, [1., 2., 3., 0., 1.], pc.Encoder(0., 4., 4, 1))
+        mask = v.valid_mask()
+        self.assertEqual(len(mask), v.nb_ciphertexts)
+        self.assertEqual(len(mask[0]), 1024)
+        self.assertEqual(mask[0], [True] * 5 + [False] * 1019)
+
+    def test_packing_four_lwes_then_extracting_each(self):
+        messages = [1., -2., 3., 0.]
+        lwe = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-4., 4., 4, 1))
+        packing_ksk = pc.RLWEKSK(self.sk, self.rsk, 2, 8)
+        packed = pc.VectorRLWE.from_vector_lwe(lwe, packing_ksk)
+        self.assertEqual(packed.nb_ciphertexts, 1)
+        extracted = [packed.extract_1_lwe(i, 0).decrypt_decode(self.sk_out)[0] for i in range(4)]
+        self.assertAllClose(extracted, messages, 0.5)
+        with self.assertRaises(ValueError):
+            pc.VectorRLWE.from_vector_lwe(
+                pc.VectorLWE.encode_encrypt(self.sk_out, messages, pc.Encoder(-4., 4., 4, 1)), packing_ksk)
+
+    def test_shape_setters_reject_invalid_values(self):
+        v = pc.VectorRLWE.encode_encrypt_packed(self.rsk, [1., 2.], pc.Encoder(0., 4., 4, 1))
+        with self.assertRaisesRegex(ValueError, 'NotPowerOfTwoError'):
+            v.polynomial_size = 500
+        with self.assertRaisesRegex(ValueError, 'DimensionError'):
+            v.polynomial_size = 1024
+        with self.assertRaisesRegex(ValueError, 'DimensionError'):
+            v.dimension = 2
+        with self.assertRaisesRegex(ValueError, 'DimensionError'):
+            v.nb_ciphertexts = 2
+        v.polynomial_size = 512
+        self.assertEqual((v.polynomial_size, v.dimension, v.nb_ciphertexts), (512, 1, 1))
+
+    def test_saved_and_unpickled_packing_keys_pack_identically(self):
+        messages = [1., -2., 3., 0.]
+        lwe = pc.VectorLWE.encode_encrypt(self.sk, messages, pc.Encoder(-4., 4., 4, 1))
+        packing_ksk = pc.RLWEKSK(self.sk, self.rsk, 2, 8)
+        with tempfile.TemporaryDirectory() as d:
+            path = os.path.join(d, 'ksk')
+            packing_ksk.save(path)
+            loaded = pc.RLWEKSK.load(path)
+            with open(path, 'r+b') as f:
+                f.truncate(64)
+            with self.assertRaises(ValueError):
+                pc.RLWEKSK.load(path)
+        for other in (loaded, pickle.loads(pickle.dumps(packing_ksk))):
+            self.assertEqual(repr(other), repr(packing_ksk))
+            packed = pc.VectorRLWE.from_vector_lwe(lwe, other)
+            self.assertEqual(pickle.dumps(packed), pickle.dumps(pc.VectorRLWE.from_vector_lwe(lwe, packing_ksk)))